@@ -1,9 +1,201 @@
 extern crate clap;
 extern crate eth_abi;
 extern crate rustc_hex as hex;
+extern crate serde_json;
 
-use eth_abi::{encode_single, ParamType};
-use hex::ToHex;
+use std::borrow::Cow;
+use std::fs;
+use std::process;
+use std::str::FromStr;
+
+use eth_abi::{
+    decode, encode, encode_hash, parse_function, parse_type_list, resolve_overload, Contract,
+    Function, ParamType, Params,
+};
+use hex::{FromHex, ToHex};
+
+/// Resolve `@path` values (curl style) into the file content: hex for
+/// `bytes`/`bytesN`, UTF-8 text for everything else.
+///
+/// Negative values can be passed as is, e.g. `-p int256 -5`. The legacy
+/// leading `~` spelling of the minus sign is still accepted for numeric
+/// types, other values are borrowed unchanged.
+fn load_value<'a>(param_type: &ParamType, value_str: &'a str) -> Result<Cow<'a, str>, String> {
+    let numeric = matches!(
+        param_type,
+        ParamType::Int(_) | ParamType::Uint(_) | ParamType::Fixed(_, _) | ParamType::Ufixed(_, _)
+    );
+    let path = match value_str.strip_prefix('@') {
+        Some(path) => path,
+        None => match value_str.strip_prefix('~') {
+            Some(magnitude) if numeric => return Ok(Cow::Owned(format!("-{}", magnitude))),
+            _ => return Ok(Cow::Borrowed(value_str)),
+        },
+    };
+    let content = fs::read(path).map_err(|e| format!("Read {} failed: {}", path, e))?;
+    let value = match param_type {
+        ParamType::Bytes | ParamType::FixedBytes(_) => Ok(format!("0x{}", content.to_hex())),
+        ParamType::String => {
+            String::from_utf8(content).map_err(|e| format!("Invalid utf8 file {}: {}", path, e))
+        }
+        _ => String::from_utf8(content)
+            .map(|s| s.trim().to_owned())
+            .map_err(|e| format!("Invalid utf8 file {}: {}", path, e)),
+    }?;
+    Ok(Cow::Owned(value))
+}
+
+fn encode_params(matches: &clap::ArgMatches) -> Result<(), String> {
+    let mut param_iter = matches
+        .values_of("param")
+        .ok_or("No params given, pass -p <type> <value> or a subcommand")?
+        .peekable();
+    let mut params = Vec::new();
+    // clap takes exactly two values per -p, so they always come in pairs
+    while param_iter.peek().is_some() {
+        let (type_str, value_str) = (param_iter.next().unwrap(), param_iter.next().unwrap());
+        println!("type={}, value={}", type_str, value_str);
+        let param_type = ParamType::from_str(type_str).map_err(|e| e.to_string())?;
+        let value = load_value(&param_type, value_str)?;
+        let encoded = encode(&param_type, &value).map_err(|e| e.to_string())?;
+        println!("[Value]: {}", encoded.to_hex());
+        params.push((param_type, value));
+    }
+    if matches.is_present("hash") {
+        let params: Vec<(ParamType, &str)> = params
+            .iter()
+            .map(|(t, v)| (t.clone(), v.as_ref()))
+            .collect();
+        let hash = encode_hash(&params).map_err(|e| e.to_string())?;
+        println!("[Hash]: 0x{}", hash.to_hex());
+    }
+    Ok(())
+}
+
+fn list_abi(matches: &clap::ArgMatches) -> Result<(), String> {
+    let path = matches.value_of("file").unwrap();
+    let json = fs::read_to_string(path).map_err(|e| format!("Read {} failed: {}", path, e))?;
+    let contract = Contract::load(&json).map_err(|e| e.to_string())?;
+    for function in &contract.functions {
+        println!(
+            "function {} 0x{}",
+            function.signature(),
+            function.selector().to_hex()
+        );
+    }
+    for event in &contract.events {
+        if event.anonymous {
+            // Anonymous events have no signature topic
+            println!("event {} anonymous", event.signature());
+        } else {
+            println!("event {} 0x{}", event.signature(), event.topic().to_hex());
+        }
+    }
+    Ok(())
+}
+
+/// Find a function by signature, or by name with overloads resolved by the
+/// given arguments
+fn find_function(contract: &Contract, name: &str, args: &[&str]) -> Result<Function, String> {
+    if name.contains('(') {
+        let signature = parse_function(name).map_err(|e| e.to_string())?.signature();
+        return contract
+            .functions
+            .iter()
+            .find(|f| f.signature() == signature)
+            .cloned()
+            .ok_or_else(|| format!("Function not found: {}", name));
+    }
+    let candidates: Vec<Function> = contract
+        .functions
+        .iter()
+        .filter(|f| f.name == name)
+        .cloned()
+        .collect();
+    match candidates.len() {
+        0 => Err(format!("Function not found: {}", name)),
+        1 => Ok(candidates[0].clone()),
+        _ => resolve_overload(&candidates, args)
+            .cloned()
+            .map_err(|e| format!("Function {} is overloaded: {}", name, e)),
+    }
+}
+
+/// Encode a call of a function from `--abi`, or of a `--function`
+/// signature alone
+fn encode_call(matches: &clap::ArgMatches) -> Result<(), String> {
+    let name = matches.value_of("function").unwrap();
+    let args: Vec<&str> = matches
+        .values_of("arg")
+        .map(|v| v.collect())
+        .unwrap_or_default();
+    let function = match matches.value_of("abi") {
+        Some(path) => {
+            let json =
+                fs::read_to_string(path).map_err(|e| format!("Read {} failed: {}", path, e))?;
+            let contract = Contract::load(&json).map_err(|e| e.to_string())?;
+            find_function(&contract, name, &args)?
+        }
+        None => parse_function(name).map_err(|e| e.to_string())?,
+    };
+    if args.len() != function.inputs.len() {
+        return Err(format!(
+            "Function {} takes {} arguments, got {}",
+            function.signature(),
+            function.inputs.len(),
+            args.len()
+        ));
+    }
+    let values = function
+        .inputs
+        .iter()
+        .zip(args)
+        .map(|(input, arg)| load_value(&input.kind, arg))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut params = Params::new();
+    for (input, value) in function.inputs.iter().zip(&values) {
+        params.push((*input.kind).clone(), value);
+    }
+    let calldata = params
+        .encode_with_selector(&function.name)
+        .map_err(|e| e.to_string())?;
+    println!("0x{}", calldata.to_hex());
+    Ok(())
+}
+
+/// Decode hex data by a comma separated type list, as `<type>: <value>`
+/// lines or, with `--format json`, a JSON array of `{"type", "value"}`
+fn decode_data(matches: &clap::ArgMatches) -> Result<(), String> {
+    let types_csv = matches.value_of("types").unwrap();
+    let data_str = matches.value_of("data").unwrap();
+    let data: Vec<u8> = data_str
+        .trim_start_matches("0x")
+        .from_hex()
+        .map_err(|e| format!("Invalid hex data: {:?}", e))?;
+    let types = parse_type_list(types_csv).map_err(|e| e.to_string())?;
+    let values = decode(&types, &data).map_err(|e| e.to_string())?;
+    if matches.value_of("format") == Some("json") {
+        let items: Vec<serde_json::Value> = types
+            .iter()
+            .zip(&values)
+            .map(|(t, v)| serde_json::json!({"type": t.to_string(), "value": v}))
+            .collect();
+        println!("{}", serde_json::Value::Array(items));
+    } else {
+        for (param_type, value) in types.iter().zip(&values) {
+            println!("{}: {}", param_type, value);
+        }
+    }
+    Ok(())
+}
+
+/// Print the canonical form and layout of a param type
+fn describe_type(matches: &clap::ArgMatches) -> Result<(), String> {
+    let type_str = matches.value_of("type").unwrap();
+    let param_type = ParamType::from_str(type_str).map_err(|e| e.to_string())?;
+    println!("{}", param_type.describe());
+    Ok(())
+}
 
 fn main() {
     let matches = clap::App::new("eth-abi CLI")
@@ -14,20 +206,95 @@ fn main() {
                 .takes_value(true)
                 .multiple(true)
                 .number_of_values(2)
-                .help("Function parameters"),
+                .allow_hyphen_values(true)
+                .help(
+                    "Function parameters as <type> <value> pairs, @file reads the value from a file, \
+                     negative values are passed as is: -p int256 -5",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("hash")
+                .long("hash")
+                .help("Also print the Keccak-256 of all params encoded together"),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("abi")
+                .about("List function selectors and event topics of a JSON ABI file")
+                .arg(
+                    clap::Arg::with_name("file")
+                        .required(true)
+                        .help("JSON ABI file"),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("encode")
+                .about("Encode a function call")
+                .arg(
+                    clap::Arg::with_name("abi")
+                        .long("abi")
+                        .takes_value(true)
+                        .help("JSON ABI file, without it --function must be a signature"),
+                )
+                .arg(
+                    clap::Arg::with_name("function")
+                        .long("function")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Function name, or signature for overloaded functions"),
+                )
+                .arg(
+                    clap::Arg::with_name("arg")
+                        .long("arg")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .allow_hyphen_values(true)
+                        .help("Function argument in declaration order, @file reads it from a file"),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("decode")
+                .about("Decode ABI encoded data")
+                .arg(
+                    clap::Arg::with_name("types")
+                        .long("types")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Comma separated types, e.g. uint256,(bool,string)"),
+                )
+                .arg(
+                    clap::Arg::with_name("format")
+                        .long("format")
+                        .takes_value(true)
+                        .possible_values(&["text", "json"])
+                        .default_value("text")
+                        .help("Output format"),
+                )
+                .arg(
+                    clap::Arg::with_name("data")
+                        .required(true)
+                        .help("Hex data, with or without 0x"),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("type")
+                .about("Show the canonical form and layout of a param type")
+                .arg(
+                    clap::Arg::with_name("type")
+                        .required(true)
+                        .help("Param type, e.g. (uint256,bool)[]"),
+                ),
         )
         .get_matches();
-    let mut param_iter = matches.values_of("param").unwrap().peekable();
-    while param_iter.peek().is_some() {
-        let (type_str, value_str) = (param_iter.next().unwrap(), param_iter.next().unwrap());
-        println!("type={}, value={}", type_str, value_str);
-        let param_type = ParamType::from_str(type_str).unwrap();
-        let value_string = value_str.replace("~", "-");
-        println!(
-            "[Value]: {}",
-            encode_single(&param_type, value_string.as_str())
-                .unwrap()
-                .to_hex()
-        );
+    let result = match matches.subcommand() {
+        ("abi", Some(sub_matches)) => list_abi(sub_matches),
+        ("encode", Some(sub_matches)) => encode_call(sub_matches),
+        ("decode", Some(sub_matches)) => decode_data(sub_matches),
+        ("type", Some(sub_matches)) => describe_type(sub_matches),
+        _ => encode_params(&matches),
+    };
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        process::exit(1);
     }
 }
@@ -0,0 +1,205 @@
+use std::env;
+use std::fs;
+use std::process::Command;
+
+fn eth_abi(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_eth-abi"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn data_path(name: &str) -> String {
+    format!("{}/tests/data/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+#[test]
+fn test_abi_list() {
+    let stdout = eth_abi(&["abi", &data_path("token.json")]);
+    assert_eq!(
+        stdout,
+        "function transfer(address,uint256) 0xa9059cbb\n\
+         event Transfer(address,address,uint256) \
+         0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef\n"
+    );
+}
+
+#[test]
+fn test_abi_list_anonymous_event() {
+    let stdout = eth_abi(&["abi", &data_path("anonymous.json")]);
+    assert_eq!(stdout, "event Deposit(address,uint256) anonymous\n");
+}
+
+#[test]
+fn test_encode_bytes_from_file() {
+    let path = env::temp_dir().join(format!("eth-abi-cli-test-{}.bin", std::process::id()));
+    fs::write(&path, [0xde, 0xad, 0xbe, 0xef]).unwrap();
+    let value = format!("@{}", path.display());
+    let stdout = eth_abi(&["-p", "bytes", &value]);
+    fs::remove_file(&path).unwrap();
+    assert!(stdout.contains(
+        "[Value]: \
+         0000000000000000000000000000000000000000000000000000000000000020\
+         0000000000000000000000000000000000000000000000000000000000000004\
+         deadbeef00000000000000000000000000000000000000000000000000000000\n"
+    ));
+}
+
+#[test]
+fn test_encode_missing_file() {
+    let path = env::temp_dir().join(format!("eth-abi-cli-missing-{}.bin", std::process::id()));
+    let value = format!("@{}", path.display());
+    let output = Command::new(env!("CARGO_BIN_EXE_eth-abi"))
+        .args(["-p", "bytes", &value])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with(&format!("Error: Read {} failed: ", path.display())));
+    assert!(!stderr.contains("panicked"));
+}
+
+#[test]
+fn test_encode_dynamic_param() {
+    let stdout = eth_abi(&["-p", "uint8[]", "[1,2]"]);
+    assert!(stdout.contains(
+        "[Value]: \
+         0000000000000000000000000000000000000000000000000000000000000020\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000002\n"
+    ));
+}
+
+#[test]
+fn test_encode_function_from_abi() {
+    let abi = data_path("token.json");
+    let stdout = eth_abi(&[
+        "encode",
+        "--abi",
+        &abi,
+        "--function",
+        "transfer",
+        "--arg",
+        "0x5b38da6a701c568545dcfcb03fcb875f56beddc4",
+        "--arg",
+        "100",
+    ]);
+    assert_eq!(
+        stdout,
+        "0xa9059cbb\
+         0000000000000000000000005b38da6a701c568545dcfcb03fcb875f56beddc4\
+         0000000000000000000000000000000000000000000000000000000000000064\n"
+    );
+}
+
+#[test]
+fn test_encode_overloaded_function_from_abi() {
+    let abi = data_path("overloaded.json");
+    let to = "0x5b38da6a701c568545dcfcb03fcb875f56beddc4";
+    let mut args = vec![
+        "encode", "--abi", &abi, "--function", "transfer", "--arg", to, "--arg", "100",
+    ];
+    let expected = eth_abi(&args);
+    assert!(expected.starts_with("0xa9059cbb"));
+
+    args[4] = "transfer(address,uint256)";
+    assert_eq!(eth_abi(&args), expected);
+
+    args[4] = "transfer";
+    args.extend(["--arg", "0xdeadbeef"].iter());
+    assert!(eth_abi(&args).starts_with("0xbe45fd62"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_eth-abi"))
+        .args(&args[..7])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("transfer is overloaded"));
+}
+
+#[test]
+fn test_encode_negative_param() {
+    let expected = "[Value]: ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff\n";
+    assert!(eth_abi(&["-p", "int256", "-1"]).contains(expected));
+    assert!(eth_abi(&["-p", "int8", "~1"]).contains(expected));
+    assert!(eth_abi(&["-p", "int8[]", "[-1]"]).contains(
+        "[Value]: \
+         0000000000000000000000000000000000000000000000000000000000000020\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff\n"
+    ));
+}
+
+#[test]
+fn test_encode_string_with_tilde() {
+    let encoded = |args: &[&str]| eth_abi(args).lines().last().unwrap().to_owned();
+    let expected = "[Value]: \
+                    0000000000000000000000000000000000000000000000000000000000000020\
+                    0000000000000000000000000000000000000000000000000000000000000004\
+                    7e617e6200000000000000000000000000000000000000000000000000000000";
+    assert_eq!(encoded(&["-p", "string", "~a~b"]), expected);
+    let stdout = eth_abi(&["encode", "--function", "f(string)", "--arg", "~a~b"]);
+    assert!(stdout.ends_with("7e617e6200000000000000000000000000000000000000000000000000000000\n"));
+}
+
+#[test]
+fn test_decode_labeled() {
+    let data = "0x\
+                00000000000000000000000000000000000000000000000000000000000005dc\
+                0000000000000000000000005b38da6a701c568545dcfcb03fcb875f56beddc4";
+    assert_eq!(
+        eth_abi(&["decode", "--types", "uint256,address", data]),
+        "uint256: 1500\n\
+         address: 0x5b38da6a701c568545dcfcb03fcb875f56beddc4\n"
+    );
+    assert_eq!(
+        eth_abi(&["decode", "--types", "uint256,address", "--format", "json", data]),
+        "[{\"type\":\"uint256\",\"value\":\"1500\"},\
+         {\"type\":\"address\",\"value\":\"0x5b38da6a701c568545dcfcb03fcb875f56beddc4\"}]\n"
+    );
+}
+
+#[test]
+fn test_encode_function_signature() {
+    assert_eq!(eth_abi(&["encode", "--function", "pause()"]), "0x8456cb59\n");
+    let to = "0x5b38da6a701c568545dcfcb03fcb875f56beddc4";
+    assert_eq!(
+        eth_abi(&["encode", "--function", "transfer(address,uint256)", "--arg", to, "--arg", "100"]),
+        eth_abi(&[
+            "encode", "--abi", &data_path("token.json"), "--function", "transfer", "--arg", to,
+            "--arg", "100",
+        ])
+    );
+}
+
+#[test]
+fn test_encode_hash() {
+    let stdout = eth_abi(&[
+        "--hash",
+        "-p",
+        "uint256",
+        "1000",
+        "-p",
+        "address",
+        "0x5b38da6a701c568545dcfcb03fcb875f56beddc4",
+    ]);
+    assert!(stdout.ends_with(
+        "[Hash]: 0xae84efedbad456cc90a19d19899e83179f981e44086479c1be5bb6badf9b39ab\n"
+    ));
+}
+
+#[test]
+fn test_describe_type() {
+    assert_eq!(
+        eth_abi(&["type", "(uint, bool)"]),
+        "(uint256,bool) (static, 64 bytes)\n"
+    );
+    assert_eq!(eth_abi(&["type", "string[]"]), "string[] (dynamic)\n");
+}
@@ -0,0 +1,23 @@
+#[macro_use]
+extern crate criterion;
+extern crate eth_abi;
+
+use criterion::{black_box, Criterion};
+use eth_abi::{ParamType, Params};
+
+fn bench_repeated_dynamic(c: &mut Criterion) {
+    let param_type: ParamType = "string[]".parse().unwrap();
+    let value_str = format!("[{}]", vec!["hello world"; 64].join(","));
+    c.bench_function("encode 256 repeated string[]", |b| {
+        b.iter(|| {
+            let mut params = Params::new();
+            for _ in 0..256 {
+                params.push(param_type.clone(), &value_str);
+            }
+            black_box(params.encode().unwrap())
+        })
+    });
+}
+
+criterion_group!(benches, bench_repeated_dynamic);
+criterion_main!(benches);
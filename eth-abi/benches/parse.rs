@@ -0,0 +1,65 @@
+#[macro_use]
+extern crate criterion;
+extern crate eth_abi;
+
+use criterion::{black_box, Criterion};
+use eth_abi::{Contract, ParamType};
+
+/// Types seen in common ABIs: tokens, DEX routers, multicall
+const TYPES: &[&str] = &[
+    "address",
+    "uint256",
+    "bool",
+    "bytes32",
+    "string",
+    "bytes",
+    "uint8",
+    "address[]",
+    "uint256[2]",
+    "bytes32[]",
+    "(address,uint256)[]",
+    "(address,bool,bytes)[]",
+    "(address,address,uint24,address,uint256,uint256,uint160)",
+    "(uint256,(bool,address[])[3])[][2]",
+];
+
+fn bench_from_str(c: &mut Criterion) {
+    c.bench_function("parse common param types", |b| {
+        b.iter(|| {
+            for type_str in TYPES {
+                black_box(type_str.parse::<ParamType>().unwrap());
+            }
+        })
+    });
+}
+
+/// JSON ABI with `count` functions all taking the same nested tuple array
+fn repeated_tuple_abi(count: usize) -> String {
+    let order = r#"{"name": "orders", "type": "tuple[]", "components": [
+        {"name": "maker", "type": "address"},
+        {"name": "amounts", "type": "uint256[2]"},
+        {"name": "fee", "type": "tuple", "components": [
+            {"name": "recipient", "type": "address"},
+            {"name": "bps", "type": "uint16"}
+        ]}
+    ]}"#;
+    let entries: Vec<String> = (0..count)
+        .map(|i| {
+            format!(
+                r#"{{"type": "function", "name": "submit{}", "inputs": [{}], "outputs": [{}]}}"#,
+                i, order, order
+            )
+        })
+        .collect();
+    format!("[{}]", entries.join(","))
+}
+
+fn bench_load_abi(c: &mut Criterion) {
+    let json = repeated_tuple_abi(200);
+    c.bench_function("load abi with repeated tuples", |b| {
+        b.iter(|| black_box(Contract::load(&json).unwrap()))
+    });
+}
+
+criterion_group!(benches, bench_from_str, bench_load_abi);
+criterion_main!(benches);
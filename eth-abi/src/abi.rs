@@ -0,0 +1,408 @@
+//! JSON ABI loading
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use super::signature::{selector_of, signature_of};
+use super::{tokenize, Error, ParamType};
+use tiny_keccak::keccak256;
+
+#[derive(Deserialize)]
+struct RawParam {
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    indexed: bool,
+    #[serde(default)]
+    components: Vec<RawParam>,
+}
+
+#[derive(Deserialize)]
+struct RawEntry {
+    #[serde(rename = "type", default = "default_entry_type")]
+    kind: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    inputs: Vec<RawParam>,
+    #[serde(default)]
+    outputs: Vec<RawParam>,
+    #[serde(default)]
+    anonymous: bool,
+}
+
+fn default_entry_type() -> String {
+    "function".to_owned()
+}
+
+/// Function input or output parameter
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    /// Parameter name, may be empty
+    pub name: String,
+    /// Parameter type, shared by all params of a contract with the same type
+    pub kind: Arc<ParamType>,
+}
+
+/// Event parameter
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventParam {
+    /// Parameter name, may be empty
+    pub name: String,
+    /// Parameter type, shared by all params of a contract with the same type
+    pub kind: Arc<ParamType>,
+    /// Whether the parameter is stored in a topic
+    pub indexed: bool,
+}
+
+/// Contract function
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    /// Function name
+    pub name: String,
+    /// Input parameters
+    pub inputs: Vec<Param>,
+    /// Output parameters
+    pub outputs: Vec<Param>,
+}
+
+impl Function {
+    /// Input parameter types
+    pub fn input_types(&self) -> Vec<ParamType> {
+        self.inputs.iter().map(|p| (*p.kind).clone()).collect()
+    }
+
+    /// Canonical signature, e.g. `transfer(address,uint256)`
+    pub fn signature(&self) -> String {
+        signature_of(&self.name, self.inputs.iter().map(|p| &*p.kind))
+    }
+
+    /// 4-byte selector
+    pub fn selector(&self) -> [u8; 4] {
+        selector_of(&self.name, self.inputs.iter().map(|p| &*p.kind))
+    }
+}
+
+/// Contract event
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    /// Event name
+    pub name: String,
+    /// Event parameters
+    pub inputs: Vec<EventParam>,
+    /// Whether the event is anonymous (has no topic0)
+    pub anonymous: bool,
+}
+
+impl Event {
+    /// Parameter types
+    pub fn input_types(&self) -> Vec<ParamType> {
+        self.inputs.iter().map(|p| (*p.kind).clone()).collect()
+    }
+
+    /// Canonical signature, e.g. `Transfer(address,address,uint256)`
+    pub fn signature(&self) -> String {
+        signature_of(&self.name, self.inputs.iter().map(|p| &*p.kind))
+    }
+
+    /// Topic0, the Keccak-256 hash of the signature
+    pub fn topic(&self) -> [u8; 32] {
+        keccak256(self.signature().as_bytes())
+    }
+}
+
+/// Functions and events of a JSON ABI
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contract {
+    /// Functions in declaration order
+    pub functions: Vec<Function>,
+    /// Events in declaration order
+    pub events: Vec<Event>,
+}
+
+/// Each type tree is built once, components are moved into their tuple
+fn parse_type(param: &RawParam) -> Result<ParamType, Error> {
+    if param.components.is_empty() {
+        return ParamType::from_str(&param.kind);
+    }
+    let components = param
+        .components
+        .iter()
+        .map(parse_type)
+        .collect::<Result<Vec<_>, Error>>()?;
+    ParamType::from_owned_components(&param.kind, components)
+}
+
+/// Key of a param type as spelled in the JSON ABI, including components
+fn type_key(param: &RawParam) -> String {
+    if param.components.is_empty() {
+        return param.kind.clone();
+    }
+    let components: Vec<String> = param.components.iter().map(type_key).collect();
+    format!("{}({})", param.kind, components.join(","))
+}
+
+/// Parse a param type once per distinct spelling, repeated types share
+/// the tree parsed first
+fn shared_type(
+    param: &RawParam,
+    types: &mut HashMap<String, Arc<ParamType>>,
+) -> Result<Arc<ParamType>, Error> {
+    let key = type_key(param);
+    if let Some(kind) = types.get(&key) {
+        return Ok(kind.clone());
+    }
+    let kind = Arc::new(parse_type(param)?);
+    types.insert(key, kind.clone());
+    Ok(kind)
+}
+
+fn parse_params(
+    params: &[RawParam],
+    types: &mut HashMap<String, Arc<ParamType>>,
+) -> Result<Vec<Param>, Error> {
+    params
+        .iter()
+        .map(|p| {
+            Ok(Param {
+                name: p.name.clone(),
+                kind: shared_type(p, types)?,
+            })
+        })
+        .collect()
+}
+
+impl Contract {
+    /// Load from JSON ABI text, entries other than functions and events are skipped
+    ///
+    /// Each distinct param type is parsed once, params of the same type
+    /// share it.
+    pub fn load(json: &str) -> Result<Self, Error> {
+        let entries: Vec<RawEntry> = serde_json::from_str(json)
+            .map_err(|e| Error::InvalidAbi(format!("Invalid ABI json: {}", e)))?;
+        let mut types = HashMap::new();
+        let mut functions = Vec::new();
+        let mut events = Vec::new();
+        for entry in entries {
+            match entry.kind.as_str() {
+                "function" => functions.push(Function {
+                    name: entry.name,
+                    inputs: parse_params(&entry.inputs, &mut types)?,
+                    outputs: parse_params(&entry.outputs, &mut types)?,
+                }),
+                "event" => {
+                    let inputs = entry
+                        .inputs
+                        .iter()
+                        .map(|p| {
+                            Ok(EventParam {
+                                name: p.name.clone(),
+                                kind: shared_type(p, &mut types)?,
+                                indexed: p.indexed,
+                            })
+                        })
+                        .collect::<Result<Vec<_>, Error>>()?;
+                    events.push(Event {
+                        name: entry.name,
+                        inputs,
+                        anonymous: entry.anonymous,
+                    })
+                }
+                _ => {}
+            }
+        }
+        Ok(Contract { functions, events })
+    }
+
+    /// Find the first function with the given name
+    pub fn function(&self, name: &str) -> Option<&Function> {
+        self.functions.iter().find(|f| f.name == name)
+    }
+
+    /// Find the first event with the given name
+    pub fn event(&self, name: &str) -> Option<&Event> {
+        self.events.iter().find(|e| e.name == name)
+    }
+}
+
+/// Pick the overload whose inputs accept `args`: the argument count must
+/// match and every argument must tokenize as its input type
+///
+/// Errors if no candidate or more than one candidate matches.
+pub fn resolve_overload<'a>(
+    candidates: &'a [Function],
+    args: &[&str],
+) -> Result<&'a Function, Error> {
+    let matched: Vec<&Function> = candidates
+        .iter()
+        .filter(|f| {
+            f.inputs.len() == args.len()
+                && f.inputs
+                    .iter()
+                    .zip(args)
+                    .all(|(input, arg)| tokenize(&input.kind, arg).is_ok())
+        })
+        .collect();
+    let signatures = |functions: &[&Function]| {
+        functions
+            .iter()
+            .map(|f| f.signature())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match matched.as_slice() {
+        [function] => Ok(function),
+        [] => {
+            let all: Vec<&Function> = candidates.iter().collect();
+            Err(Error::InvalidValue(format!(
+                "No overload matches {} arguments, candidates: {}",
+                args.len(),
+                signatures(&all)
+            )))
+        }
+        _ => Err(Error::InvalidValue(format!(
+            "Ambiguous overloads: {}",
+            signatures(&matched)
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use hex::ToHex;
+
+    const ERC20_ABI: &str = r#"[
+        {
+            "type": "function",
+            "name": "transfer",
+            "inputs": [
+                {"name": "to", "type": "address"},
+                {"name": "value", "type": "uint256"}
+            ],
+            "outputs": [{"name": "", "type": "bool"}]
+        },
+        {"type": "constructor", "inputs": []},
+        {
+            "type": "event",
+            "name": "Transfer",
+            "anonymous": false,
+            "inputs": [
+                {"name": "from", "type": "address", "indexed": true},
+                {"name": "to", "type": "address", "indexed": true},
+                {"name": "value", "type": "uint256", "indexed": false}
+            ]
+        }
+    ]"#;
+
+    #[test]
+    fn test_load_contract() {
+        let contract = Contract::load(ERC20_ABI).unwrap();
+        assert_eq!(contract.functions.len(), 1);
+        assert_eq!(contract.events.len(), 1);
+
+        let function = contract.function("transfer").unwrap();
+        assert_eq!(function.signature(), "transfer(address,uint256)");
+        assert_eq!(function.selector().to_hex(), "a9059cbb");
+        assert_eq!(*function.outputs[0].kind, ParamType::Bool);
+
+        let event = contract.event("Transfer").unwrap();
+        assert_eq!(event.signature(), "Transfer(address,address,uint256)");
+        assert!(event.inputs[0].indexed);
+        assert!(!event.inputs[2].indexed);
+    }
+
+    #[test]
+    fn test_load_invalid_contract() {
+        assert!(Contract::load("{}").is_err());
+        assert!(Contract::load(r#"[{"type": "function", "name": "f", "inputs": [{"type": "uint7"}]}]"#).is_err());
+    }
+
+    #[test]
+    fn test_resolve_overload() {
+        let candidates = [
+            ::parse_function("transfer(address,uint256)").unwrap(),
+            ::parse_function("transfer(address,bool)").unwrap(),
+            ::parse_function("transfer(address,uint256,bytes)").unwrap(),
+        ];
+        let to = "0x5b38da6a701c568545dcfcb03fcb875f56beddc4";
+        let resolve = |args: &[&str]| resolve_overload(&candidates, args).map(|f| f.signature());
+        assert_eq!(resolve(&[to, "100"]), Ok("transfer(address,uint256)".to_owned()));
+        assert_eq!(resolve(&[to, "true"]), Ok("transfer(address,bool)".to_owned()));
+        assert_eq!(
+            resolve(&[to, "100", "0x"]),
+            Ok("transfer(address,uint256,bytes)".to_owned())
+        );
+        assert!(resolve(&[to]).is_err());
+        assert!(resolve(&["0x01", "100"]).is_err());
+
+        let candidates = [
+            ::parse_function("transfer(uint256)").unwrap(),
+            ::parse_function("transfer(int256)").unwrap(),
+        ];
+        assert_eq!(
+            resolve_overload(&candidates, &["-1"]).map(|f| f.signature()),
+            Ok("transfer(int256)".to_owned())
+        );
+        match resolve_overload(&candidates, &["1"]) {
+            Err(Error::InvalidValue(message)) => assert_eq!(
+                message,
+                "Ambiguous overloads: transfer(uint256), transfer(int256)"
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_load_tuple_components() {
+        let json = r#"[{
+            "type": "function",
+            "name": "submit",
+            "inputs": [{
+                "name": "orders",
+                "type": "tuple[]",
+                "components": [
+                    {"name": "maker", "type": "address"},
+                    {"name": "amounts", "type": "uint256[2]"}
+                ]
+            }]
+        }]"#;
+        let contract = Contract::load(json).unwrap();
+        let function = contract.function("submit").unwrap();
+        assert_eq!(
+            *function.inputs[0].kind,
+            ParamType::Array(Box::new(ParamType::Tuple(vec![
+                ParamType::Address,
+                ParamType::FixedArray(Box::new(ParamType::Uint(256)), 2),
+            ])))
+        );
+        assert_eq!(function.signature(), "submit((address,uint256[2])[])");
+    }
+
+    #[test]
+    fn test_load_shares_repeated_types() {
+        let order = r#"{"type": "tuple[]", "components": [
+            {"name": "maker", "type": "address"},
+            {"name": "amounts", "type": "uint256[2]"}
+        ]}"#;
+        let json = format!(
+            r#"[
+                {{"type": "function", "name": "a", "inputs": [{0}], "outputs": [{0}]}},
+                {{"type": "function", "name": "b", "inputs": [{0}, {{"type": "uint256"}}]}},
+                {{"type": "event", "name": "C", "inputs": [{0}, {{"type": "uint8[2]"}}]}}
+            ]"#,
+            order
+        );
+        let contract = Contract::load(&json).unwrap();
+        let first = &contract.functions[0].inputs[0].kind;
+        assert!(Arc::ptr_eq(first, &contract.functions[0].outputs[0].kind));
+        assert!(Arc::ptr_eq(first, &contract.functions[1].inputs[0].kind));
+        assert!(Arc::ptr_eq(first, &contract.events[0].inputs[0].kind));
+        assert!(!Arc::ptr_eq(first, &contract.events[0].inputs[1].kind));
+        assert_eq!(contract.events[0].signature(), "C((address,uint256[2])[],uint8[2])");
+    }
+}
@@ -0,0 +1,77 @@
+//! `Result<_, String>` wrappers of the functions that used to return
+//! string errors, kept for migrating to `Error`
+
+#![allow(deprecated)]
+
+use std::str::FromStr;
+
+use super::{Bytes, ParamType, Params};
+
+/// `ParamType::from_str` with a string error
+#[deprecated(note = "use ParamType::from_str, which returns Error")]
+pub fn parse_type_str_err(s: &str) -> Result<ParamType, String> {
+    ParamType::from_str(s).map_err(|e| e.to_string())
+}
+
+/// `encode_single` with a string error
+#[deprecated(note = "use encode_single, which returns Error")]
+pub fn encode_single_str_err(param_type: &ParamType, value_str: &str) -> Result<Bytes, String> {
+    ::encode_single(param_type, value_str).map_err(|e| e.to_string())
+}
+
+/// `encode` with a string error
+#[deprecated(note = "use encode, which returns Error")]
+pub fn encode_str_err(param_type: &ParamType, value_str: &str) -> Result<Bytes, String> {
+    ::encode(param_type, value_str).map_err(|e| e.to_string())
+}
+
+/// `encode_params` with a string error, without the param location
+/// `encode_params` adds
+#[deprecated(note = "use encode_params, which returns Error")]
+pub fn encode_params_str_err(params: &[(ParamType, &str)]) -> Result<Bytes, String> {
+    ::encode_params(params).map_err(|e| e.root().to_string())
+}
+
+/// `Params::encode` with a string error, without the param location
+/// `Params::encode` adds
+#[deprecated(note = "use Params::encode, which returns Error")]
+pub fn params_encode_str_err(params: &mut Params) -> Result<Bytes, String> {
+    params.encode().map_err(|e| e.root().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_string_errors() {
+        assert_eq!(
+            parse_type_str_err("foo"),
+            Err("Invalid param type: foo".to_owned())
+        );
+        assert_eq!(
+            encode_single_str_err(&ParamType::Bool, "yes"),
+            Err("Invalid value for bool: yes".to_owned())
+        );
+        assert_eq!(
+            encode_single_str_err(&ParamType::Array(Box::new(ParamType::Bool)), "[yes]"),
+            Err("Invalid value for bool: yes".to_owned())
+        );
+        let param_type = ParamType::Uint(256);
+        assert_eq!(
+            encode_str_err(&param_type, "1"),
+            Ok(::encode(&param_type, "1").unwrap())
+        );
+        assert_eq!(
+            encode_params_str_err(&[(ParamType::Bool, "yes")]),
+            Err("Invalid value for bool: yes".to_owned())
+        );
+        let mut params = Params::new();
+        params.push(ParamType::Bool, "yes");
+        assert_eq!(
+            params_encode_str_err(&mut params),
+            Err("Invalid value for bool: yes".to_owned())
+        );
+    }
+}
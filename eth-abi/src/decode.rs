@@ -0,0 +1,1340 @@
+//! Decode ABI encoded data into tokens or value strings
+
+use ethereum_types::{Address, U256};
+use hex::ToHex;
+use std::str::FromStr;
+
+use super::{format_units, parse_function, Error, ParamType, Token, TokenRef, WORD_SIZE};
+use token::{fits_signed, split_signed};
+
+/// Options controlling how decoded values are rendered
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DecodeOptions {
+    /// Render unsigned integers as `0x` hex instead of decimal
+    pub uint_as_hex: bool,
+    /// Decode any non-zero `bool` word as `true`, instead of rejecting
+    /// words other than 0 and 1
+    pub lenient_bool: bool,
+    /// Ignore non-zero high 12 bytes of an `address` word, instead of
+    /// rejecting them
+    pub lenient_address: bool,
+    /// Ignore dirty padding instead of rejecting it: bits of an integer
+    /// word beyond its width, which are dropped as Solidity does, and
+    /// non-zero bytes after fixed bytes, `bytes` or `string` content
+    pub lenient_padding: bool,
+}
+
+impl DecodeOptions {
+    /// Options tolerating every non-canonical encoding
+    pub fn lenient() -> Self {
+        DecodeOptions {
+            lenient_bool: true,
+            lenient_address: true,
+            lenient_padding: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Decode params, unconsumed trailing bytes are ignored
+///
+/// Non-canonical encodings are rejected, see `DecodeOptions` to tolerate
+/// them. A `bool` word must be exactly 0 or 1, an `address` word must have
+/// zero high bytes and padding must be zero. Integer words must fit their
+/// declared width: zero high bytes beyond it, or a valid sign extension for
+/// signed types.
+/// `fixed<M>x<N>`/`ufixed<M>x<N>` values are rendered as decimals with
+/// trailing fractional zeros trimmed, e.g. `1.5` rather than `1.500`.
+///
+/// A top-level param list is laid out like a tuple of the params, so a
+/// static `[Tuple(vec![A, B])]` decodes the same bytes as `[A, B]`, as one
+/// `(a,b)` value instead of two. A dynamic tuple is preceded by its offset
+/// word, unlike the param list.
+pub fn decode(types: &[ParamType], data: &[u8]) -> Result<Vec<String>, Error> {
+    decode_with_options(types, data, &DecodeOptions::default())
+}
+
+/// Decode a single value encoded as a one element param list, e.g. the
+/// return data of `balanceOf`. The inverse of `encode`.
+pub fn decode_single(param_type: &ParamType, data: &[u8]) -> Result<String, Error> {
+    let mut values = decode(std::slice::from_ref(param_type), data)?;
+    Ok(values.remove(0))
+}
+
+/// Decode params with the given options, unconsumed trailing bytes are ignored
+pub fn decode_with_options(
+    types: &[ParamType],
+    data: &[u8],
+    options: &DecodeOptions,
+) -> Result<Vec<String>, Error> {
+    #[cfg(feature = "tracing")]
+    let _span = debug_span!(
+        "decode",
+        types = %::signature::signature_of("", types),
+        len = data.len()
+    )
+    .entered();
+    let decoded = decode_sequence(types, data, 0, None, options);
+    #[cfg(feature = "tracing")]
+    match &decoded {
+        Ok((_, end)) => trace!(consumed = *end, "decoded"),
+        Err(e) => debug!(error = %e, "decode failed"),
+    }
+    let (tokens, _) = decoded?;
+    Ok(format_tokens(types, &into_tokens(tokens), options))
+}
+
+/// Decode params into tokens, unconsumed trailing bytes are ignored
+///
+/// `fixed<M>x<N>`/`ufixed<M>x<N>` values are returned as their raw
+/// `Token::Int`/`Token::Uint` words.
+pub fn decode_tokens(types: &[ParamType], data: &[u8]) -> Result<Vec<Token>, Error> {
+    decode_token_refs(types, data).map(into_tokens)
+}
+
+/// Decode params into tokens borrowing `bytes`, `string` and fixed bytes
+/// content from `data`, unconsumed trailing bytes are ignored
+///
+/// Nothing is copied out of `data`, which saves allocations when only a
+/// few values of large return data are inspected.
+pub fn decode_token_refs<'a>(
+    types: &[ParamType],
+    data: &'a [u8],
+) -> Result<Vec<TokenRef<'a>>, Error> {
+    decode_sequence(types, data, 0, None, &DecodeOptions::default()).map(|(tokens, _)| tokens)
+}
+
+fn into_tokens(tokens: Vec<TokenRef>) -> Vec<Token> {
+    tokens.into_iter().map(Token::from).collect()
+}
+
+/// Decode params given as a comma separated type list, e.g.
+/// `uint256,(bool,string)`, see `parse_type_list`
+pub fn decode_by_sig(types_csv: &str, data: &[u8]) -> Result<Vec<String>, Error> {
+    decode(&parse_type_list(types_csv)?, data)
+}
+
+/// Parse a comma separated type list, e.g. `uint256,(bool,string)`
+pub fn parse_type_list(types_csv: &str) -> Result<Vec<ParamType>, Error> {
+    let types = ParamType::from_str(&format!("({})", types_csv)).map_err(|e| match e {
+        // Positions relative to `types_csv`, without the added parenthesis
+        Error::InvalidType { message, position } => Error::InvalidType {
+            message,
+            position: position.saturating_sub(1),
+        },
+        e => e,
+    })?;
+    match types {
+        ParamType::Tuple(types) => Ok(types),
+        _ => Err(Error::InvalidType {
+            message: format!("Invalid type list: {}", types_csv),
+            position: 0,
+        }),
+    }
+}
+
+/// Decode as many leading params as possible, for guessing at unknown
+/// signatures
+///
+/// Returns the values decoded before the first failing param, and the error
+/// of that param if any; its index is the number of returned values.
+pub fn decode_best_effort(types: &[ParamType], data: &[u8]) -> (Vec<String>, Option<Error>) {
+    let options = DecodeOptions::default();
+    let mut values = Vec::new();
+    let mut head = 0;
+    for param_type in types {
+        match decode_item(param_type, data, 0, &mut head, &options) {
+            Ok((token, _)) => values.push(format_token(param_type, &token.into(), &options)),
+            Err(e) => return (values, Some(e)),
+        }
+    }
+    (values, None)
+}
+
+/// Decode params, also returning the bytes after the furthest region read,
+/// e.g. extra data a protocol appends to the ABI encoded section
+pub fn decode_with_remainder<'a>(
+    types: &[ParamType],
+    data: &'a [u8],
+) -> Result<(Vec<String>, &'a [u8]), Error> {
+    let options = DecodeOptions::default();
+    let (tokens, end) = decode_sequence(types, data, 0, None, &options)?;
+    Ok((format_tokens(types, &into_tokens(tokens), &options), &data[end..]))
+}
+
+/// Decode params, error if unconsumed trailing bytes remain
+pub fn decode_strict(types: &[ParamType], data: &[u8]) -> Result<Vec<String>, Error> {
+    let options = DecodeOptions::default();
+    let (tokens, end) = decode_sequence(types, data, 0, None, &options)?;
+    if end < data.len() {
+        return Err(Error::InvalidData(format!(
+            "Unconsumed trailing bytes: consumed={}, total={}",
+            end,
+            data.len()
+        )));
+    }
+    Ok(format_tokens(types, &into_tokens(tokens), &options))
+}
+
+/// Packed size in bytes of a scalar type, `None` for types without a fixed
+/// packed size
+pub(crate) fn packed_size(param_type: &ParamType) -> Option<usize> {
+    match param_type {
+        ParamType::Address => Some(20),
+        ParamType::Bool => Some(1),
+        ParamType::Int(m)
+        | ParamType::Uint(m)
+        | ParamType::Fixed(m, _)
+        | ParamType::Ufixed(m, _) => Some(m / 8),
+        ParamType::FixedBytes(m) => Some(*m),
+        _ => None,
+    }
+}
+
+/// Decode `abi.encodePacked` output
+///
+/// Packed encoding drops lengths and padding, so it can only be reversed
+/// for a list of scalar types, optionally ending in a single `bytes` or
+/// `string` which takes the remaining bytes. Any other layout, e.g. two
+/// `string`s or an array, is ambiguous and rejected with
+/// `Error::Unsupported`.
+pub fn decode_packed(types: &[ParamType], data: &[u8]) -> Result<Vec<String>, Error> {
+    let mut values = Vec::new();
+    let mut pos = 0;
+    for (i, param_type) in types.iter().enumerate() {
+        let size = match (packed_size(param_type), param_type) {
+            (Some(size), _) => size,
+            (None, ParamType::Bytes) | (None, ParamType::String) if i + 1 == types.len() => {
+                let rest = &data[pos..];
+                values.push(match param_type {
+                    ParamType::Bytes => format!("0x{}", rest.to_hex()),
+                    _ => String::from_utf8(rest.to_vec())
+                        .map_err(|e| Error::InvalidData(format!("Invalid utf8 string: {:?}", e)))?,
+                });
+                return Ok(values);
+            }
+            _ => {
+                return Err(Error::Unsupported(format!(
+                    "Cannot decode packed {}: packed encoding is only reversible for scalar types \
+                     optionally followed by one trailing bytes or string",
+                    param_type
+                )))
+            }
+        };
+        if pos + size > data.len() {
+            return Err(Error::OutOfBounds {
+                position: pos,
+                length: size,
+                total: data.len(),
+            });
+        }
+        let value_bytes = &data[pos..pos + size];
+        // Widen into a standard word and reuse the regular decoding
+        let signed = matches!(param_type, ParamType::Int(_) | ParamType::Fixed(_, _));
+        let fill = if signed && value_bytes[0] & 0x80 != 0 { 0xff } else { 0 };
+        let mut word = vec![fill; WORD_SIZE];
+        match param_type {
+            ParamType::FixedBytes(_) => word[..size].copy_from_slice(value_bytes),
+            _ => word[WORD_SIZE - size..].copy_from_slice(value_bytes),
+        }
+        let options = DecodeOptions::default();
+        let (token, _) = decode_value(param_type, &word, 0, &options)?;
+        values.push(format_token(param_type, &token.into(), &options));
+        pos += size;
+    }
+    if pos < data.len() {
+        return Err(Error::InvalidData(format!(
+            "Unconsumed trailing bytes: consumed={}, total={}",
+            pos,
+            data.len()
+        )));
+    }
+    Ok(values)
+}
+
+/// Lazily iterate the elements of a `uint256[]` located at `offset` (the
+/// position of its length word, as pointed to by its head slot), without
+/// collecting them. Bounds are checked once upfront.
+pub fn decode_uint_array_iter<'a>(
+    data: &'a [u8],
+    offset: usize,
+) -> Result<impl Iterator<Item = U256> + 'a, Error> {
+    let len = read_length(data, offset, WORD_SIZE)?;
+    let start = offset + WORD_SIZE;
+    let end = start + len * WORD_SIZE;
+    Ok(data[start..end].chunks(WORD_SIZE).map(U256::from))
+}
+
+/// A dynamic array whose elements are decoded on demand, see `decode_array`
+#[derive(Debug, Clone)]
+pub struct DecodedArray<'a> {
+    element: ParamType,
+    data: &'a [u8],
+    start: usize,
+    len: usize,
+}
+
+/// Lazily decode a `T[]` of `element` located at `offset` (the position of
+/// its length word, as pointed to by its head slot)
+///
+/// Only the length and the bounds of the element heads are checked upfront,
+/// each element is decoded and checked when it is accessed.
+pub fn decode_array<'a>(
+    element: &ParamType,
+    data: &'a [u8],
+    offset: usize,
+) -> Result<DecodedArray<'a>, Error> {
+    let head_size = element.encode_type_component_count() * WORD_SIZE;
+    Ok(DecodedArray {
+        element: element.clone(),
+        data,
+        start: offset + WORD_SIZE,
+        len: read_length(data, offset, head_size)?,
+    })
+}
+
+impl<'a> DecodedArray<'a> {
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the array has no elements
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Decode the element at `index`, `None` if out of range
+    pub fn get(&self, index: usize) -> Option<Result<TokenRef<'a>, Error>> {
+        if index >= self.len {
+            return None;
+        }
+        let head_size = self.element.encode_type_component_count() * WORD_SIZE;
+        let mut head = self.start + index * head_size;
+        let options = DecodeOptions::default();
+        Some(
+            decode_item(&self.element, self.data, self.start, &mut head, &options)
+                .map(|(token, _)| token)
+                .map_err(|e| e.context(format!("at index {} of {}[]", index, self.element))),
+        )
+    }
+
+    /// Iterate the elements, decoding each one as it is reached
+    pub fn iter<'s>(&'s self) -> impl Iterator<Item = Result<TokenRef<'a>, Error>> + 's {
+        (0..self.len).filter_map(move |i| self.get(i))
+    }
+}
+
+/// Decode the return data of `Multicall.aggregate`, the outer
+/// `(uint256 blockNumber, bytes[] returnData)`, then each `returnData` blob
+/// with the return types of its call
+pub fn decode_multicall_result(
+    data: &[u8],
+    return_types: &[Vec<ParamType>],
+) -> Result<Vec<Vec<String>>, Error> {
+    let outer_types = [
+        ParamType::Uint(256),
+        ParamType::Array(Box::new(ParamType::Bytes)),
+    ];
+    let return_data = match decode_tokens(&outer_types, data)?.pop() {
+        Some(Token::Array(return_data)) => return_data,
+        _ => unreachable!("decoded as bytes[]"),
+    };
+    if return_data.len() != return_types.len() {
+        return Err(Error::InvalidData(format!(
+            "Multicall result count mismatch: expected={}, actual={}",
+            return_types.len(),
+            return_data.len()
+        )));
+    }
+    return_data
+        .iter()
+        .zip(return_types)
+        .map(|(blob, types)| match blob {
+            Token::Bytes(blob) => decode(types, blob),
+            _ => unreachable!("decoded as bytes"),
+        })
+        .collect()
+}
+
+/// Standard revert data shapes
+#[derive(Debug, Clone, PartialEq)]
+pub enum Revert {
+    /// `Error(string)`, from `require`/`revert` with a reason
+    Error(String),
+    /// `Panic(uint256)`, from failed assertions, overflows and the like
+    Panic(U256),
+}
+
+fn strip_selector<'a>(data: &'a [u8], expected: &[u8; 4]) -> Result<&'a [u8], Error> {
+    if data.len() < 4 {
+        return Err(Error::InvalidData(format!(
+            "Data too short for a selector: length={}",
+            data.len()
+        )));
+    }
+    if &data[..4] != expected {
+        return Err(Error::InvalidData(format!(
+            "Selector mismatch: expected=0x{}, actual=0x{}",
+            expected.to_hex(),
+            data[..4].to_hex()
+        )));
+    }
+    Ok(&data[4..])
+}
+
+/// Check calldata starts with the selector of function `name` taking
+/// `types`, returning the argument bytes after it
+pub fn verify_selector<'a>(
+    data: &'a [u8],
+    name: &str,
+    types: &[ParamType],
+) -> Result<&'a [u8], Error> {
+    strip_selector(data, &::selector(name, types))
+}
+
+/// Decode custom error revert data: the leading 4 bytes must be the
+/// selector of `sig`, e.g. `InsufficientBalance(uint256,uint256)`, and the
+/// remaining bytes are decoded as its arguments
+pub fn decode_error(sig: &str, data: &[u8]) -> Result<Vec<String>, Error> {
+    let error = parse_function(sig)?;
+    decode(&error.input_types(), strip_selector(data, &error.selector())?)
+}
+
+/// Decode standard `Error(string)` or `Panic(uint256)` revert data
+pub fn decode_revert(data: &[u8]) -> Result<Revert, Error> {
+    if data.len() >= 4 && data[..4] == ::selector("Panic", &[ParamType::Uint(256)]) {
+        let code = U256::from(read_word(&data[4..], 0)?);
+        return Ok(Revert::Panic(code));
+    }
+    let mut values = decode_error("Error(string)", data)?;
+    Ok(Revert::Error(values.remove(0)))
+}
+
+fn read_word(data: &[u8], pos: usize) -> Result<&[u8], Error> {
+    match pos.checked_add(WORD_SIZE) {
+        Some(end) if end <= data.len() => Ok(&data[pos..end]),
+        _ => Err(Error::OutOfBounds {
+            position: pos,
+            length: WORD_SIZE,
+            total: data.len(),
+        }),
+    }
+}
+
+/// Read the offset word at `head` and resolve it against `base`, it must
+/// point inside the data
+fn read_offset(data: &[u8], head: usize, base: usize) -> Result<usize, Error> {
+    let offset = U256::from(read_word(data, head)?);
+    let pos = if offset <= U256::from(data.len()) {
+        base.checked_add(offset.low_u64() as usize)
+    } else {
+        None
+    };
+    match pos {
+        Some(pos) if pos <= data.len() => Ok(pos),
+        _ => Err(Error::InvalidOffset {
+            position: head,
+            offset,
+            total: data.len(),
+        }),
+    }
+}
+
+/// Read the length word at `pos` of content with `item_size` bytes per
+/// item, which must fit in the data after the length word
+///
+/// Zero sized items, e.g. empty tuples, are still limited to one item per
+/// byte of data, so a crafted length can't make decoding loop for long.
+fn read_length(data: &[u8], pos: usize, item_size: usize) -> Result<usize, Error> {
+    let length = U256::from(read_word(data, pos)?);
+    let max_length = match item_size {
+        0 => data.len(),
+        _ => (data.len() - pos - WORD_SIZE) / item_size,
+    };
+    if length > U256::from(max_length) {
+        return Err(Error::InvalidDataLength {
+            position: pos,
+            length,
+            total: data.len(),
+        });
+    }
+    Ok(length.low_u64() as usize)
+}
+
+/// Format `value / 10^decimals`, trailing fractional zeros are trimmed
+fn format_decimal(value: U256, decimals: usize) -> String {
+    format_units(value, decimals as u32)
+}
+
+/// Decode a head/tail encoded sequence starting at `base`, returning the
+/// values and the end of the furthest region read.
+///
+/// Errors of the items of a `parent` array or tuple get their index as
+/// context.
+fn decode_sequence<'t, 'a, I>(
+    types: I,
+    data: &'a [u8],
+    base: usize,
+    parent: Option<&ParamType>,
+    options: &DecodeOptions,
+) -> Result<(Vec<TokenRef<'a>>, usize), Error>
+where
+    I: IntoIterator<Item = &'t ParamType>,
+{
+    let mut values = Vec::new();
+    let mut head = base;
+    let mut end = base;
+    for (i, param_type) in types.into_iter().enumerate() {
+        let (value, value_end) =
+            decode_item(param_type, data, base, &mut head, options).map_err(|e| match parent {
+                Some(parent @ ParamType::Tuple(_)) => {
+                    e.context(format!("in member {} of {}", i, parent))
+                }
+                Some(parent) => e.context(format!("at index {} of {}", i, parent)),
+                None => e,
+            })?;
+        values.push(value);
+        end = end.max(head).max(value_end);
+    }
+    Ok((values, end))
+}
+
+/// Decode the sequence item whose head slot is at `head`, following its
+/// offset if it has one, and advance `head` past the slot.
+fn decode_item<'a>(
+    param_type: &ParamType,
+    data: &'a [u8],
+    base: usize,
+    head: &mut usize,
+    options: &DecodeOptions,
+) -> Result<(TokenRef<'a>, usize), Error> {
+    if param_type.needs_offset() {
+        let pos = read_offset(data, *head, base)?;
+        *head += WORD_SIZE;
+        decode_value(param_type, data, pos, options)
+    } else {
+        let (value, value_end) = decode_value(param_type, data, *head, options)?;
+        *head = value_end;
+        Ok((value, value_end))
+    }
+}
+
+fn decode_bytes<'a>(
+    data: &'a [u8],
+    pos: usize,
+    options: &DecodeOptions,
+) -> Result<(&'a [u8], usize), Error> {
+    let len = read_length(data, pos, 1)?;
+    let start = pos + WORD_SIZE;
+    // The content fits, so only the padding of its last word can be missing
+    let end = start + len.next_multiple_of(WORD_SIZE);
+    if end > data.len() {
+        return Err(Error::InvalidDataLength {
+            position: pos,
+            length: U256::from(len),
+            total: data.len(),
+        });
+    }
+    check_padding(&data[start + len..end], start + len, options)?;
+    Ok((&data[start..start + len], end))
+}
+
+/// Check the padding at `pos` is zero, unless `lenient_padding` is set
+fn check_padding(padding: &[u8], pos: usize, options: &DecodeOptions) -> Result<(), Error> {
+    if !options.lenient_padding && padding.iter().any(|b| *b != 0) {
+        return Err(Error::InvalidData(format!(
+            "Non-zero padding: position={}, padding=0x{}",
+            pos,
+            padding.to_hex()
+        )));
+    }
+    Ok(())
+}
+
+/// Keep the low `bits` of `value`, sign extended for signed types
+fn clean_int(value: U256, bits: usize, signed: bool) -> U256 {
+    if bits == 256 {
+        return value;
+    }
+    let mask = (U256::one() << bits) - U256::one();
+    let low = value & mask;
+    if signed && low.bit(bits - 1) {
+        low | !mask
+    } else {
+        low
+    }
+}
+
+/// Error for a word with non-zero high bytes, or bad sign extension for
+/// signed types, beyond the declared width
+fn invalid_int(param_type: &ParamType, pos: usize, value: U256) -> Error {
+    Error::InvalidData(format!(
+        "Invalid {} value: position={}, value={:#x}",
+        param_type, pos, value
+    ))
+}
+
+/// Decode a single value located at `pos`, returning the token and the end
+/// of the region it occupies.
+fn decode_value<'a>(
+    param_type: &ParamType,
+    data: &'a [u8],
+    pos: usize,
+    options: &DecodeOptions,
+) -> Result<(TokenRef<'a>, usize), Error> {
+    match param_type {
+        ParamType::Address => {
+            let word = read_word(data, pos)?;
+            if !options.lenient_address && word[..12].iter().any(|b| *b != 0) {
+                return Err(Error::InvalidData(format!(
+                    "Invalid address value: position={}, value=0x{}",
+                    pos,
+                    word.to_hex()
+                )));
+            }
+            let mut address = [0u8; 20];
+            address.copy_from_slice(&word[12..]);
+            Ok((TokenRef::Address(Address::from(address)), pos + WORD_SIZE))
+        }
+        ParamType::Uint(bits) | ParamType::Ufixed(bits, _) => {
+            let value = U256::from(read_word(data, pos)?);
+            if value.bits() > *bits {
+                if !options.lenient_padding {
+                    return Err(invalid_int(param_type, pos, value));
+                }
+                return Ok((TokenRef::Uint(clean_int(value, *bits, false)), pos + WORD_SIZE));
+            }
+            Ok((TokenRef::Uint(value), pos + WORD_SIZE))
+        }
+        ParamType::Int(bits) | ParamType::Fixed(bits, _) => {
+            let value = U256::from(read_word(data, pos)?);
+            if !fits_signed(value, *bits) {
+                if !options.lenient_padding {
+                    return Err(invalid_int(param_type, pos, value));
+                }
+                return Ok((TokenRef::Int(clean_int(value, *bits, true)), pos + WORD_SIZE));
+            }
+            Ok((TokenRef::Int(value), pos + WORD_SIZE))
+        }
+        ParamType::Bool => {
+            let value = U256::from(read_word(data, pos)?);
+            if !options.lenient_bool && value > U256::one() {
+                return Err(Error::InvalidData(format!(
+                    "Invalid bool value: position={}, value={}",
+                    pos, value
+                )));
+            }
+            Ok((TokenRef::Bool(!value.is_zero()), pos + WORD_SIZE))
+        }
+        ParamType::FixedBytes(m) => {
+            let word = read_word(data, pos)?;
+            check_padding(&word[*m..], pos + m, options)?;
+            Ok((TokenRef::FixedBytes(&word[..*m]), pos + WORD_SIZE))
+        }
+        ParamType::Bytes => {
+            let (value_bytes, end) = decode_bytes(data, pos, options)?;
+            Ok((TokenRef::Bytes(value_bytes), end))
+        }
+        ParamType::String => {
+            let (value_bytes, end) = decode_bytes(data, pos, options)?;
+            let value = std::str::from_utf8(value_bytes)
+                .map_err(|e| Error::InvalidData(format!("Invalid utf8 string: {:?}", e)))?;
+            Ok((TokenRef::String(value), end))
+        }
+        ParamType::Array(subtype) => {
+            let head_size = subtype.encode_type_component_count() * WORD_SIZE;
+            let len = read_length(data, pos, head_size)?;
+            let types = std::iter::repeat_n(&**subtype, len);
+            let (tokens, end) =
+                decode_sequence(types, data, pos + WORD_SIZE, Some(param_type), options)?;
+            Ok((TokenRef::Array(tokens), end))
+        }
+        ParamType::FixedArray(subtype, len) => {
+            let types = std::iter::repeat_n(&**subtype, *len);
+            let (tokens, end) = decode_sequence(types, data, pos, Some(param_type), options)?;
+            Ok((TokenRef::FixedArray(tokens), end))
+        }
+        ParamType::Tuple(subtypes) => {
+            let (tokens, end) = decode_sequence(subtypes, data, pos, Some(param_type), options)?;
+            Ok((TokenRef::Tuple(tokens), end))
+        }
+    }
+}
+
+fn format_tokens(types: &[ParamType], tokens: &[Token], options: &DecodeOptions) -> Vec<String> {
+    types
+        .iter()
+        .zip(tokens)
+        .map(|(param_type, token)| format_token(param_type, token, options))
+        .collect()
+}
+
+/// Render a decoded token as a value string, `param_type` must be the type
+/// it was decoded with
+fn format_token(param_type: &ParamType, token: &Token, options: &DecodeOptions) -> String {
+    match (param_type, token) {
+        (ParamType::Ufixed(_, n), Token::Uint(value)) => format_decimal(*value, *n),
+        (ParamType::Fixed(_, n), Token::Int(value)) => {
+            let (negative, value) = split_signed(*value);
+            let sign = if negative { "-" } else { "" };
+            format!("{}{}", sign, format_decimal(value, *n))
+        }
+        (_, Token::Uint(value)) if options.uint_as_hex => format!("{:#x}", value),
+        (ParamType::Array(subtype), Token::Array(tokens))
+        | (ParamType::FixedArray(subtype, _), Token::FixedArray(tokens)) => {
+            let values: Vec<String> = tokens
+                .iter()
+                .map(|token| format_token(subtype, token, options))
+                .collect();
+            format!("[{}]", values.join(","))
+        }
+        (ParamType::Tuple(subtypes), Token::Tuple(tokens)) => {
+            format!("({})", format_tokens(subtypes, tokens, options).join(","))
+        }
+        (_, Token::FixedArray(_)) | (_, Token::Array(_)) | (_, Token::Tuple(_)) => {
+            unreachable!("Decoded token does not match type {}", param_type)
+        }
+        (_, token) => token.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use hex::FromHex;
+
+    fn uint_string_data() -> Vec<u8> {
+        "0000000000000000000000000000000000000000000000000000000000000003\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000003\
+         6162630000000000000000000000000000000000000000000000000000000000"
+            .from_hex()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_decode() {
+        let types = vec![ParamType::Uint(256), ParamType::String];
+        assert_eq!(
+            decode(&types, &uint_string_data()),
+            Ok(vec!["3".to_owned(), "abc".to_owned()])
+        );
+        assert_eq!(
+            decode_strict(&types, &uint_string_data()),
+            Ok(vec!["3".to_owned(), "abc".to_owned()])
+        );
+    }
+
+    #[test]
+    fn test_decode_token_refs() {
+        let types = [
+            ParamType::from_str("bytes2").unwrap(),
+            ParamType::from_str("(string,bytes)[]").unwrap(),
+        ];
+        let params = [(types[0].clone(), "0xbeef"), (types[1].clone(), "[(abc,0xdead)]")];
+        let data = ::encode_params(&params).unwrap();
+        let tokens = decode_token_refs(&types, &data).unwrap();
+        let content = match &tokens[1] {
+            TokenRef::Array(elements) => match &elements[0] {
+                TokenRef::Tuple(members) => members.clone(),
+                other => panic!("unexpected token: {:?}", other),
+            },
+            other => panic!("unexpected token: {:?}", other),
+        };
+        assert_eq!(tokens[0], TokenRef::FixedBytes(&[0xbe, 0xef]));
+        assert_eq!(content, vec![TokenRef::String("abc"), TokenRef::Bytes(&[0xde, 0xad])]);
+        // Content points into the input instead of a copy
+        let range = data.as_ptr_range();
+        match content[1] {
+            TokenRef::Bytes(value_bytes) => assert!(range.contains(&value_bytes.as_ptr())),
+            ref other => panic!("unexpected token: {:?}", other),
+        }
+        let owned: Vec<Token> = tokens.into_iter().map(Token::from).collect();
+        assert_eq!(decode_tokens(&types, &data), Ok(owned));
+    }
+
+    #[test]
+    fn test_decode_single() {
+        let data = ::encode(&ParamType::Uint(256), "1500").unwrap();
+        assert_eq!(decode_single(&ParamType::Uint(256), &data), Ok("1500".to_owned()));
+        let param_type = ParamType::from_str("string[]").unwrap();
+        let data = ::encode(&param_type, "[a,bc]").unwrap();
+        assert_eq!(decode_single(&param_type, &data), Ok("[a,bc]".to_owned()));
+        assert!(decode_single(&ParamType::Uint(256), &data[..31]).is_err());
+    }
+
+    #[test]
+    fn test_decode_best_effort() {
+        let data: Vec<u8> = "0000000000000000000000000000000000000000000000000000000000000003\
+             0000000000000000000000000000000000000000000000000000000000000060\
+             0000000000000000000000000000000000000000000000000000000000000005\
+             0000000000000000000000000000000000000000000000000000000000000003\
+             6162630000000000000000000000000000000000000000000000000000000000"
+            .from_hex()
+            .unwrap();
+        let types = [ParamType::Uint(256), ParamType::String, ParamType::Uint(8)];
+        assert_eq!(
+            decode_best_effort(&types, &data),
+            (vec!["3".to_owned(), "abc".to_owned(), "5".to_owned()], None)
+        );
+
+        let types = [ParamType::Uint(256), ParamType::String, ParamType::Bool];
+        let (values, error) = decode_best_effort(&types, &data);
+        assert_eq!(values, vec!["3".to_owned(), "abc".to_owned()]);
+        assert_eq!(
+            error,
+            Some(Error::InvalidData(
+                "Invalid bool value: position=64, value=5".to_owned()
+            ))
+        );
+        assert!(decode(&types, &data).is_err());
+    }
+
+    #[test]
+    fn test_decode_int_width() {
+        let word = |hex_str: &str| -> Vec<u8> { hex_str.from_hex().unwrap() };
+        let max_uint64 = word("000000000000000000000000000000000000000000000000ffffffffffffffff");
+        let dirty_uint64 = word("000000000000000000000000000000000000000000000001ffffffffffffffff");
+        assert_eq!(
+            decode(&[ParamType::Uint(64)], &max_uint64),
+            Ok(vec!["18446744073709551615".to_owned()])
+        );
+        assert_eq!(
+            decode(&[ParamType::Uint(64)], &dirty_uint64),
+            Err(Error::InvalidData(
+                "Invalid uint64 value: position=0, value=0x1ffffffffffffffff".to_owned()
+            ))
+        );
+
+        let min_int64 = word("ffffffffffffffffffffffffffffffffffffffffffffffff8000000000000000");
+        let bad_negative = word("fffffffffffffffffffffffffffffffffffffffffffffffe8000000000000000");
+        let bad_positive = word("0000000000000000000000000000000000000000000000008000000000000000");
+        assert_eq!(
+            decode(&[ParamType::Int(64)], &min_int64),
+            Ok(vec!["-9223372036854775808".to_owned()])
+        );
+        assert!(decode(&[ParamType::Int(64)], &bad_negative).is_err());
+        assert!(decode(&[ParamType::Int(64)], &bad_positive).is_err());
+        assert!(decode(&[ParamType::Int(256)], &bad_negative).is_ok());
+    }
+
+    #[test]
+    fn test_decode_multicall_result() {
+        let balance = ::encode_params(&[(ParamType::Uint(256), "5")]).unwrap();
+        let info = ::encode_params(&[(ParamType::Bool, "true"), (ParamType::String, "abc")])
+            .unwrap();
+        let return_data = format!("[0x{},0x{}]", balance.to_hex(), info.to_hex());
+        let data = ::encode_params(&[
+            (ParamType::Uint(256), "100"),
+            (ParamType::Array(Box::new(ParamType::Bytes)), &return_data),
+        ]).unwrap();
+        let return_types = [
+            vec![ParamType::Uint(256)],
+            vec![ParamType::Bool, ParamType::String],
+        ];
+        assert_eq!(
+            decode_multicall_result(&data, &return_types),
+            Ok(vec![
+                vec!["5".to_owned()],
+                vec!["true".to_owned(), "abc".to_owned()],
+            ])
+        );
+        assert!(decode_multicall_result(&data, &return_types[..1]).is_err());
+    }
+
+    #[test]
+    fn test_decode_with_remainder() {
+        let types = vec![ParamType::Uint(256), ParamType::String];
+        let mut data = uint_string_data();
+        assert_eq!(
+            decode_with_remainder(&types, &data),
+            Ok((vec!["3".to_owned(), "abc".to_owned()], &[][..]))
+        );
+        data.extend_from_slice(&[0xca, 0xfe]);
+        assert_eq!(
+            decode_with_remainder(&types, &data),
+            Ok((vec!["3".to_owned(), "abc".to_owned()], &[0xca, 0xfe][..]))
+        );
+        assert_eq!(
+            decode_with_remainder(&types[..1], &data).map(|(_, rest)| rest.len()),
+            Ok(data.len() - 32)
+        );
+    }
+
+    #[test]
+    fn test_decode_tuple_as_param_list() {
+        let types = vec![ParamType::Uint(256), ParamType::Bool];
+        let tuple = vec![ParamType::Tuple(types.clone())];
+        let data = ::encode_params(&[(types[0].clone(), "7"), (types[1].clone(), "true")]).unwrap();
+        assert_eq!(data, ::encode_params(&[(tuple[0].clone(), "(7,true)")]).unwrap());
+        assert_eq!(decode(&types, &data), Ok(vec!["7".to_owned(), "true".to_owned()]));
+        assert_eq!(decode(&tuple, &data), Ok(vec!["(7,true)".to_owned()]));
+    }
+
+    #[test]
+    fn test_decode_error_context() {
+        let encoded_type = ParamType::from_str("(uint256,uint256[])").unwrap();
+        let data = ::encode_params(&[(encoded_type, "(1,[1,2,256])")]).unwrap();
+        let types = vec![ParamType::from_str("(uint256,uint8[])").unwrap()];
+        let err = decode(&types, &data).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "in member 1 of (uint256,uint8[]): at index 2 of uint8[]: \
+             Invalid uint8 value: position=192, value=0x100"
+        );
+        match err.root() {
+            Error::InvalidData(_) => {}
+            other => panic!("unexpected root error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_by_sig() {
+        let types = [
+            ParamType::Uint(256),
+            ParamType::from_str("(bool,string)").unwrap(),
+        ];
+        let data = ::encode_params(&[
+            (types[0].clone(), "3"),
+            (types[1].clone(), "(true,abc)"),
+        ]).unwrap();
+        assert_eq!(
+            decode_by_sig("uint256, (bool,string)", &data),
+            Ok(vec!["3".to_owned(), "(true,abc)".to_owned()])
+        );
+        assert_eq!(parse_type_list("uint256, (bool,string)"), Ok(types.to_vec()));
+        assert_eq!(decode_by_sig("", &[]), Ok(vec![]));
+        assert!(decode_by_sig("uint256,(bool", &data).is_err());
+    }
+
+    #[test]
+    fn test_decode_trailing_bytes() {
+        let types = vec![ParamType::Uint(256), ParamType::String];
+        let mut data = uint_string_data();
+        data.extend(vec![0u8; 32]);
+        assert_eq!(
+            decode(&types, &data),
+            Ok(vec!["3".to_owned(), "abc".to_owned()])
+        );
+        assert!(decode_strict(&types, &data).is_err());
+    }
+
+    #[test]
+    fn test_decode_fixed() {
+        // 1.5 * 10^18
+        let data = "00000000000000000000000000000000000000000000000014d1120d7b160000"
+            .from_hex()
+            .unwrap();
+        assert_eq!(
+            decode(&[ParamType::Ufixed(128, 18)], &data),
+            Ok(vec!["1.5".to_owned()])
+        );
+
+        // -0.25 * 10^18
+        let data = "fffffffffffffffffffffffffffffffffffffffffffffffffc87d25316270000"
+            .from_hex()
+            .unwrap();
+        assert_eq!(
+            decode(&[ParamType::Fixed(128, 18)], &data),
+            Ok(vec!["-0.25".to_owned()])
+        );
+    }
+
+    #[test]
+    fn test_decode_fixed_arrays() {
+        let data = ::encode_params(&[(
+            ParamType::from_str("uint256[]").unwrap(),
+            "[1500000000000000000,2000000000000000000]",
+        )])
+        .unwrap();
+        assert_eq!(
+            decode(&[ParamType::from_str("ufixed128x18[]").unwrap()], &data),
+            Ok(vec!["[1.5,2]".to_owned()])
+        );
+
+        let data = ::encode_params(&[(ParamType::from_str("int256[2]").unwrap(), "[-25,10]")])
+            .unwrap();
+        assert_eq!(
+            decode(&[ParamType::from_str("fixed128x2[2]").unwrap()], &data),
+            Ok(vec!["[-0.25,0.1]".to_owned()])
+        );
+    }
+
+    #[test]
+    fn test_format_decimal() {
+        assert_eq!(format_decimal(U256::from(1500), 3), "1.5");
+        assert_eq!(format_decimal(U256::from(2000), 3), "2");
+        assert_eq!(format_decimal(U256::from(5), 3), "0.005");
+        assert_eq!(format_decimal(U256::from(0), 3), "0");
+        assert_eq!(format_decimal(U256::from(42), 0), "42");
+    }
+
+    #[test]
+    fn test_decode_uint_array_iter() {
+        let values: Vec<String> = (1..=100).map(|n| n.to_string()).collect();
+        let value_str = format!("[{}]", values.join(","));
+        let array_type = ParamType::Array(Box::new(ParamType::Uint(256)));
+        let data = ::encode_params(&[(array_type, value_str.as_str())]).unwrap();
+
+        let iter = decode_uint_array_iter(&data, 32).unwrap();
+        let sum = iter.fold(U256::zero(), |acc, n| acc + n);
+        assert_eq!(sum, U256::from(5050));
+
+        assert!(decode_uint_array_iter(&data[..data.len() - 1], 32).is_err());
+    }
+
+    #[test]
+    fn test_decode_array() {
+        let values: Vec<String> = (1..=100).map(|n| n.to_string()).collect();
+        let value_str = format!("[{}]", values.join(","));
+        let data = ::encode(&ParamType::from_str("uint256[]").unwrap(), &value_str).unwrap();
+        let array = decode_array(&ParamType::Uint(256), &data, 32).unwrap();
+        assert_eq!(array.len(), 100);
+        assert_eq!(array.get(99), Some(Ok(TokenRef::Uint(U256::from(100)))));
+        assert_eq!(array.get(100), None);
+        let sum = array.iter().fold(U256::zero(), |acc, token| match token {
+            Ok(TokenRef::Uint(n)) => acc + n,
+            other => panic!("unexpected token: {:?}", other),
+        });
+        assert_eq!(sum, U256::from(5050));
+        assert!(decode_array(&ParamType::Uint(256), &data[..data.len() - 1], 32).is_err());
+
+        // Dynamic elements are only followed when accessed
+        let mut data = ::encode(&ParamType::from_str("string[]").unwrap(), "[a,bc]").unwrap();
+        let offset_end = 4 * WORD_SIZE;
+        data[offset_end - 1] = 0xff;
+        let array = decode_array(&ParamType::String, &data, 32).unwrap();
+        assert_eq!(array.get(0), Some(Ok(TokenRef::String("a"))));
+        match array.get(1) {
+            Some(Err(e)) => assert!(e.to_string().starts_with("at index 1 of string[]: ")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_uint_as_hex() {
+        let types = vec![ParamType::Uint(256), ParamType::Array(Box::new(ParamType::Uint(8)))];
+        let data = ::encode_params(&[
+            (types[0].clone(), "0xff00"),
+            (types[1].clone(), "[0,16]"),
+        ]).unwrap();
+        assert_eq!(
+            decode(&types, &data),
+            Ok(vec!["65280".to_owned(), "[0,16]".to_owned()])
+        );
+        let options = DecodeOptions {
+            uint_as_hex: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            decode_with_options(&types, &data, &options),
+            Ok(vec!["0xff00".to_owned(), "[0x0,0x10]".to_owned()])
+        );
+    }
+
+    #[test]
+    fn test_decode_unaligned_bytes() {
+        let types = vec![ParamType::Bytes, ParamType::Uint(8)];
+        let payload = format!("0x{}", "ab".repeat(33));
+        let data = ::encode_params(&[
+            (types[0].clone(), payload.as_str()),
+            (types[1].clone(), "7"),
+        ]).unwrap();
+        // Offset, 7, length 33, then two padded data words
+        assert_eq!(data.len(), 32 * 5);
+        assert_eq!(
+            decode_strict(&types, &data),
+            Ok(vec![payload.clone(), "7".to_owned()])
+        );
+        // The padding of the last word is required even though the payload
+        // fits in fewer bytes
+        assert_eq!(
+            decode(&types, &data[..data.len() - 31]),
+            Err(Error::InvalidDataLength {
+                position: 64,
+                length: U256::from(33),
+                total: 129
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_bool() {
+        let types = vec![ParamType::Bool];
+        let mut data = vec![0u8; 32];
+        data[31] = 1;
+        assert_eq!(decode(&types, &data), Ok(vec!["true".to_owned()]));
+        data[31] = 2;
+        assert_eq!(
+            decode(&types, &data),
+            Err(Error::InvalidData(
+                "Invalid bool value: position=0, value=2".to_owned()
+            ))
+        );
+        let options = DecodeOptions {
+            lenient_bool: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            decode_with_options(&types, &data, &options),
+            Ok(vec!["true".to_owned()])
+        );
+    }
+
+    #[test]
+    fn test_decode_lenient() {
+        let cases = [
+            (ParamType::Address, 0, "0x00000000000000000000000000000000000000ff"),
+            (ParamType::Uint(8), 0, "255"),
+            (ParamType::Int(8), 0, "-1"),
+            (ParamType::FixedBytes(2), 31, "0xbeef"),
+            (ParamType::String, 95, "abc"),
+        ];
+        for (param_type, dirty, expected) in &cases {
+            let mut data = ::encode(param_type, expected).unwrap();
+            data[*dirty] = 0x01;
+            let types = [param_type.clone()];
+            assert!(decode(&types, &data).is_err(), "{} accepted", param_type);
+            assert_eq!(
+                decode_with_options(&types, &data, &DecodeOptions::lenient()),
+                Ok(vec![expected.to_string()])
+            );
+        }
+        assert_eq!(
+            decode(&[ParamType::Bytes], &::encode(&ParamType::Bytes, "0xdead").unwrap()),
+            Ok(vec!["0xdead".to_owned()])
+        );
+    }
+
+    #[test]
+    fn test_decode_malicious_offsets() {
+        let word = |n: u64| ::encode(&ParamType::Uint(256), &n.to_string()).unwrap();
+        let max_word = vec![0xff; WORD_SIZE];
+        let bytes = [ParamType::Bytes];
+
+        assert_eq!(
+            decode(&bytes, &[word(0x20), word(2)].concat()),
+            Err(Error::InvalidDataLength {
+                position: 32,
+                length: U256::from(2),
+                total: 64
+            })
+        );
+        assert_eq!(
+            decode(&bytes, &[word(0x60), word(0)].concat()),
+            Err(Error::InvalidOffset {
+                position: 0,
+                offset: U256::from(0x60),
+                total: 64
+            })
+        );
+        match decode(&bytes, &[max_word.clone(), word(0)].concat()) {
+            Err(Error::InvalidOffset { offset, .. }) => assert_eq!(offset, U256::max_value()),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match decode(&bytes, &[word(0x20), max_word.clone()].concat()) {
+            Err(Error::InvalidDataLength { length, .. }) => assert_eq!(length, U256::max_value()),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(
+            decode(&[ParamType::Uint(256)], &[0u8; 31]),
+            Err(Error::OutOfBounds {
+                position: 0,
+                length: 32,
+                total: 31
+            })
+        );
+        // A huge length of zero sized elements must not loop
+        let empty_tuples = [ParamType::from_str("()[]").unwrap()];
+        let data = [word(0x20), max_word.clone()].concat();
+        assert!(decode(&empty_tuples, &data).is_err());
+        let data = [word(0x20), word(3)].concat();
+        assert_eq!(decode(&empty_tuples, &data), Ok(vec!["[(),(),()]".to_owned()]));
+
+        // No single corrupted word makes decoding panic
+        let types = [
+            ParamType::from_str("(string,bytes[],uint8[2])[]").unwrap(),
+            ParamType::String,
+        ];
+        let data = ::encode_params(&[
+            (types[0].clone(), "[(ab,[0x01,0x0203],[1,2]),(c,[],[3,4])]"),
+            (types[1].clone(), "xyz"),
+        ])
+        .unwrap();
+        for i in 0..data.len() / WORD_SIZE {
+            for fill in &[max_word.clone(), word(0x7fff_ffff), word(data.len() as u64 - 1)] {
+                let mut corrupted = data.clone();
+                corrupted[i * WORD_SIZE..(i + 1) * WORD_SIZE].copy_from_slice(fill);
+                let _ = decode(&types, &corrupted);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_packed() {
+        let types = vec![
+            ParamType::Address,
+            ParamType::Uint(16),
+            ParamType::Int(8),
+            ParamType::Bool,
+            ParamType::FixedBytes(2),
+            ParamType::String,
+        ];
+        let data = "00000000000000000000000000000000000000ff\
+                    0102\
+                    fe\
+                    01\
+                    abcd\
+                    616263"
+            .from_hex()
+            .unwrap();
+        assert_eq!(
+            decode_packed(&types, &data),
+            Ok(vec![
+                "0x00000000000000000000000000000000000000ff".to_owned(),
+                "258".to_owned(),
+                "-2".to_owned(),
+                "true".to_owned(),
+                "0xabcd".to_owned(),
+                "abc".to_owned(),
+            ])
+        );
+        assert!(decode_packed(&types[..5], &data).is_err());
+        assert!(decode_packed(&types, &data[..20]).is_err());
+    }
+
+    #[test]
+    fn test_decode_packed_ambiguous() {
+        let data = "61626364".from_hex().unwrap();
+        match decode_packed(&[ParamType::String, ParamType::String], &data) {
+            Err(Error::Unsupported(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let types = vec![ParamType::Array(Box::new(ParamType::Uint(8)))];
+        match decode_packed(&types, &data) {
+            Err(Error::Unsupported(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_error() {
+        let sig = "InsufficientBalance(uint256,uint256)";
+        let types = vec![ParamType::Uint(256), ParamType::Uint(256)];
+        let mut data = ::selector("InsufficientBalance", &types).to_vec();
+        data.extend(::encode_params(&[(types[0].clone(), "100"), (types[1].clone(), "250")]).unwrap());
+        assert_eq!(
+            decode_error(sig, &data),
+            Ok(vec!["100".to_owned(), "250".to_owned()])
+        );
+        assert!(decode_error("Unauthorized(address)", &data).is_err());
+        assert!(decode_error(sig, &data[..3]).is_err());
+    }
+
+    #[test]
+    fn test_verify_selector() {
+        let types = vec![ParamType::Address, ParamType::Uint(256)];
+        let args = ::encode_params(&[
+            (types[0].clone(), "0x5b38da6a701c568545dcfcb03fcb875f56beddc4"),
+            (types[1].clone(), "100"),
+        ])
+        .unwrap();
+        let mut data = ::selector("transfer", &types).to_vec();
+        data.extend(&args);
+        assert_eq!(verify_selector(&data, "transfer", &types), Ok(&args[..]));
+        assert_eq!(
+            verify_selector(&data, "approve", &types),
+            Err(Error::InvalidData(
+                "Selector mismatch: expected=0x095ea7b3, actual=0xa9059cbb".to_owned()
+            ))
+        );
+        assert!(verify_selector(&data[..2], "transfer", &types).is_err());
+    }
+
+    #[test]
+    fn test_decode_revert() {
+        let data = "08c379a0\
+                    0000000000000000000000000000000000000000000000000000000000000020\
+                    000000000000000000000000000000000000000000000000000000000000000a\
+                    4e6f7420656e6f75676800000000000000000000000000000000000000000000"
+            .from_hex()
+            .unwrap();
+        assert_eq!(decode_revert(&data), Ok(Revert::Error("Not enough".to_owned())));
+
+        let data = "4e487b71\
+                    0000000000000000000000000000000000000000000000000000000000000011"
+            .from_hex()
+            .unwrap();
+        assert_eq!(decode_revert(&data), Ok(Revert::Panic(U256::from(0x11))));
+    }
+
+    #[test]
+    fn test_decode_tuple_array() {
+        // (uint256,bool)[] with [(1,true),(2,false)]
+        let data = "0000000000000000000000000000000000000000000000000000000000000020\
+                    0000000000000000000000000000000000000000000000000000000000000002\
+                    0000000000000000000000000000000000000000000000000000000000000001\
+                    0000000000000000000000000000000000000000000000000000000000000001\
+                    0000000000000000000000000000000000000000000000000000000000000002\
+                    0000000000000000000000000000000000000000000000000000000000000000"
+            .from_hex()
+            .unwrap();
+        let param_type = ParamType::Array(Box::new(ParamType::Tuple(vec![
+            ParamType::Uint(256),
+            ParamType::Bool,
+        ])));
+        assert_eq!(
+            decode_strict(&[param_type], &data),
+            Ok(vec!["[(1,true),(2,false)]".to_owned()])
+        );
+
+        // Dynamic tuple elements are laid out through an offset table
+        let param_type = ParamType::Array(Box::new(ParamType::Tuple(vec![
+            ParamType::Uint(256),
+            ParamType::String,
+        ])));
+        let data = ::encode(&param_type, "[(1,a),(2,bc)]").unwrap();
+        assert_eq!(
+            decode_strict(&[param_type], &data),
+            Ok(vec!["[(1,a),(2,bc)]".to_owned()])
+        );
+    }
+
+    #[test]
+    fn test_decode_tokens() {
+        let params = [
+            (ParamType::Address, "0x5b38da6a701c568545dcfcb03fcb875f56beddc4"),
+            (ParamType::Uint(8), "255"),
+            (ParamType::Int(16), "-2"),
+            (ParamType::Bool, "true"),
+            (ParamType::FixedBytes(2), "0xbeef"),
+            (ParamType::Bytes, "0xdead"),
+            (ParamType::String, "abc"),
+            (ParamType::Ufixed(128, 2), "1.5"),
+            (ParamType::Fixed(128, 2), "-1.5"),
+            (ParamType::from_str("uint8[]").unwrap(), "[1,2]"),
+            (ParamType::from_str("bool[2]").unwrap(), "[true,false]"),
+            (ParamType::from_str("(uint8,string)").unwrap(), "(7,x)"),
+        ];
+        let data = ::encode_params(&params).unwrap();
+        let types: Vec<ParamType> = params.iter().map(|(t, _)| t.clone()).collect();
+        let expected = params
+            .iter()
+            .map(|(t, v)| ::tokenize(t, v))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decode_tokens(&types, &data), Ok(expected));
+
+        // Malformed data is an error, never a panic
+        assert!(decode_tokens(&types, &data[..data.len() - 1]).is_err());
+        assert!(decode_tokens(&types, &data[..WORD_SIZE * 3]).is_err());
+        let mut bad_offset = ::encode_params(&[(ParamType::Bytes, "0xdead")]).unwrap();
+        bad_offset[WORD_SIZE - 1] = 0xff;
+        assert!(decode_tokens(&[ParamType::Bytes], &bad_offset).is_err());
+        let mut bad_length = ::encode_params(&[(ParamType::Bytes, "0xdead")]).unwrap();
+        bad_length[WORD_SIZE..WORD_SIZE * 2].copy_from_slice(&[0xff; WORD_SIZE]);
+        assert!(decode_tokens(&[ParamType::Bytes], &bad_length).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! Error type
+
+use ethereum_types::U256;
+use std::error;
+use std::fmt;
+
+/// Errors returned by parsing and encoding
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// Malformed or unknown param type string
+    InvalidType {
+        /// Error message
+        message: String,
+        /// Byte offset of the failing component in the type string
+        position: usize,
+    },
+    /// Value can not be parsed as the given param type
+    InvalidValue(String),
+    /// Value is out of range for the given param type
+    Overflow(String),
+    /// Value has a different length than required
+    InvalidLength {
+        /// Required length in bytes
+        expected: usize,
+        /// Actual length in bytes
+        actual: usize,
+    },
+    /// Operation is not supported for the given param type
+    Unsupported(String),
+    /// Encoded data is malformed
+    InvalidData(String),
+    /// Encoded data ends before a value read from it
+    OutOfBounds {
+        /// Byte position of the read
+        position: usize,
+        /// Bytes to read
+        length: usize,
+        /// Total bytes of the data
+        total: usize,
+    },
+    /// Offset word pointing outside the encoded data
+    InvalidOffset {
+        /// Byte position of the offset word
+        position: usize,
+        /// Offset relative to the start of the enclosing sequence
+        offset: U256,
+        /// Total bytes of the data
+        total: usize,
+    },
+    /// Length word claiming more content than the encoded data holds
+    InvalidDataLength {
+        /// Byte position of the length word
+        position: usize,
+        /// Claimed number of bytes or elements
+        length: U256,
+        /// Total bytes of the data
+        total: usize,
+    },
+    /// JSON ABI is malformed
+    InvalidAbi(String),
+    /// Writing encoded data failed
+    Io(String),
+    /// Error with the location it occurred at
+    Context {
+        /// Location, e.g. `at index 2 of uint256[]`
+        context: String,
+        /// Underlying error
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Attach the location the error occurred at, outer locations are
+    /// attached last and displayed first
+    pub fn context<C: Into<String>>(self, context: C) -> Error {
+        Error::Context {
+            context: context.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// The underlying error without any context
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::Context { source, .. } => source.root(),
+            e => e,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidType { message: msg, .. }
+            | Error::InvalidValue(msg)
+            | Error::Overflow(msg)
+            | Error::Unsupported(msg)
+            | Error::InvalidData(msg)
+            | Error::InvalidAbi(msg)
+            | Error::Io(msg) => write!(f, "{}", msg),
+            Error::InvalidLength { expected, actual } => write!(
+                f,
+                "Invalid value length: expected={}, actual={}",
+                expected, actual
+            ),
+            Error::OutOfBounds {
+                position,
+                length,
+                total,
+            } => write!(
+                f,
+                "Read out of bounds: position={}, length={}, total={}",
+                position, length, total
+            ),
+            Error::InvalidOffset {
+                position,
+                offset,
+                total,
+            } => write!(
+                f,
+                "Offset out of bounds: position={}, offset={}, total={}",
+                position, offset, total
+            ),
+            Error::InvalidDataLength {
+                position,
+                length,
+                total,
+            } => write!(
+                f,
+                "Length out of bounds: position={}, length={}, total={}",
+                position, length, total
+            ),
+            Error::Context { context, source } => write!(f, "{}: {}", context, source),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Context { source, .. } => Some(&**source),
+            _ => None,
+        }
+    }
+}
@@ -5,14 +5,64 @@
 
 extern crate ethereum_types;
 extern crate rustc_hex as hex;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate tiny_keccak;
+#[cfg(feature = "tracing")]
+#[macro_use]
+extern crate tracing;
 
-use ethereum_types::U256;
-use hex::FromHex;
+mod abi;
+pub mod compat;
+mod decode;
+mod error;
+mod native;
+mod signature;
+mod token;
+mod tokenize;
+mod units;
+
+use hex::ToHex;
+use std::cell::Cell;
+use std::collections::hash_map::{Entry, HashMap};
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokenize::{parse_bytes, tokenize_scalar};
+
+pub use abi::{resolve_overload, Contract, Event, EventParam, Function, Param};
+pub use decode::{
+    decode, decode_array, decode_best_effort, decode_by_sig, decode_error,
+    decode_multicall_result, decode_packed, decode_revert, decode_single, decode_strict,
+    decode_token_refs, decode_tokens, decode_uint_array_iter, decode_with_options,
+    decode_with_remainder, parse_type_list, verify_selector, DecodeOptions, DecodedArray, Revert,
+};
+pub use error::Error;
+pub use ethereum_types::{Address, U256};
+pub use native::{decode_native, NativeValue};
+pub use signature::{event_topic, parse_function, selector, selector_from_sig, signature};
+pub use token::{
+    encode_tokens, encode_tokens_unchecked, type_check, EncodeOptions, Token, TokenRef,
+};
+pub use tokenize::{tokenize, tokenize_json, DefaultTokenizer, RadixTokenizer, Tokenizer};
+pub use units::{format_units, parse_units};
 
 type Bytes = Vec<u8>;
 
+/// Size in bytes of an ABI word, the unit of the head/tail layout
+const WORD_SIZE: usize = 32;
+
+/// Round `len` up to a whole number of words
+pub(crate) fn pad_to_word(len: usize) -> usize {
+    len.next_multiple_of(WORD_SIZE)
+}
+
 /// Function parameter type enum
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ParamType {
     /// Address
     Address,
@@ -24,9 +74,9 @@ pub enum ParamType {
     Uint(usize),
     /// Boolean
     Bool,
-    /// TODO: fixed<M>x<N>: Signed fixed-point decimal number
+    /// Signed fixed-point decimal number `fixed<M>x<N>`: `M` bits, `N` decimals
     Fixed(usize, usize),
-    /// TODO: Unsigned variant of fixed<M>x<N>
+    /// Unsigned variant of fixed<M>x<N>
     Ufixed(usize, usize),
     /// String
     String,
@@ -40,27 +90,195 @@ pub enum ParamType {
     Tuple(Vec<ParamType>),
 }
 
-impl ParamType {
+impl fmt::Display for ParamType {
+    /// Canonical type name, as used in function signatures
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParamType::Address => write!(f, "address"),
+            ParamType::Bytes => write!(f, "bytes"),
+            ParamType::Int(m) => write!(f, "int{}", m),
+            ParamType::Uint(m) => write!(f, "uint{}", m),
+            ParamType::Bool => write!(f, "bool"),
+            ParamType::Fixed(m, n) => write!(f, "fixed{}x{}", m, n),
+            ParamType::Ufixed(m, n) => write!(f, "ufixed{}x{}", m, n),
+            ParamType::String => write!(f, "string"),
+            ParamType::Array(subtype) => write!(f, "{}[]", subtype),
+            ParamType::FixedBytes(m) => write!(f, "bytes{}", m),
+            ParamType::FixedArray(subtype, len) => write!(f, "{}[{}]", subtype, len),
+            ParamType::Tuple(subtypes) => {
+                let names: Vec<String> = subtypes.iter().map(|t| t.to_string()).collect();
+                write!(f, "({})", names.join(","))
+            }
+        }
+    }
+}
+
+impl FromStr for ParamType {
+    type Err = Error;
+
     /// Parse type from string
-    pub fn from_str(s: &str) -> Result<Self, String> {
-        if s.ends_with("[]") {
-            let subtype = Self::from_str(&s[..(s.len() - 2)])?;
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Self::parse_with(s, &|_| None)
+    }
+}
+
+/// Check that `[]` and `()` are balanced and properly nested, errors report
+/// the position of the stray or unclosed bracket
+fn check_brackets(s: &str) -> Result<(), Error> {
+    let mut open = Vec::new();
+    for (i, c) in s.char_indices() {
+        match c {
+            '[' | '(' => open.push((i, c)),
+            ']' | ')' => {
+                let expected = if c == ']' { '[' } else { '(' };
+                match open.pop() {
+                    Some((_, o)) if o == expected => {}
+                    _ => {
+                        return Err(Error::InvalidType {
+                            message: format!("Invalid param type: {}, unbalanced '{}'", s, c),
+                            position: i,
+                        })
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    match open.pop() {
+        Some((i, c)) => Err(Error::InvalidType {
+            message: format!("Invalid param type: {}, unclosed '{}'", s, c),
+            position: i,
+        }),
+        None => Ok(()),
+    }
+}
+
+fn parse_type_number(s: &str, num: &str) -> Result<usize, Error> {
+    num.parse::<usize>().map_err(|e| Error::InvalidType {
+        message: format!("Invalid param type: {}, {:?}", s, e),
+        position: 0,
+    })
+}
+
+/// Parse the `M` of `int<M>`/`uint<M>`
+fn parse_int_width(s: &str, num: &str) -> Result<usize, Error> {
+    check_int_width(s, parse_type_number(s, num)?)
+}
+
+fn check_int_width(s: &str, width: usize) -> Result<usize, Error> {
+    if !(8..=256).contains(&width) || !width.is_multiple_of(8) {
+        return Err(Error::InvalidType {
+            message: format!(
+                "Invalid param type: {}, width must be between 8 and 256 and a multiple of 8, got {}",
+                s, width
+            ),
+            position: 0,
+        });
+    }
+    Ok(width)
+}
+
+/// Parse the `<M>x<N>` of `fixed<M>x<N>`/`ufixed<M>x<N>`
+fn parse_fixed_dims(s: &str, dims: &str) -> Result<(usize, usize), Error> {
+    let (bits, decimals) = dims.split_once('x').ok_or_else(|| Error::InvalidType {
+        message: format!("Invalid param type: {}, expected <M>x<N>", s),
+        position: 0,
+    })?;
+    let bits = parse_int_width(s, bits)?;
+    let decimals = parse_type_number(s, decimals)?;
+    if decimals == 0 || decimals > 80 {
+        return Err(Error::InvalidType {
+            message: format!(
+                "Invalid param type: {}, decimals must be between 1 and 80, got {}",
+                s, decimals
+            ),
+            position: 0,
+        });
+    }
+    Ok((bits, decimals))
+}
+
+impl<'a> TryFrom<&'a str> for ParamType {
+    type Error = Error;
+
+    fn try_from(s: &'a str) -> Result<Self, Error> {
+        Self::from_str(s)
+    }
+}
+
+impl TryFrom<String> for ParamType {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self, Error> {
+        Self::from_str(&s)
+    }
+}
+
+impl ParamType {
+    /// Parse type from string, unknown identifiers (e.g. struct, enum or
+    /// user defined value type names) are passed to `resolve`
+    pub fn parse_with(s: &str, resolve: &dyn Fn(&str) -> Option<ParamType>) -> Result<Self, Error> {
+        check_brackets(s)?;
+        Self::parse_at(s, 0, resolve)
+    }
+
+    /// Parse `s` located at byte `offset` of the whole type string, errors
+    /// report the position of the failing component
+    ///
+    /// The rightmost array suffix is the outermost type, as in Solidity:
+    /// `uint256[5][2]` is two `uint256[5]`, i.e.
+    /// `FixedArray(FixedArray(Uint(256), 5), 2)`.
+    fn parse_at(
+        s: &str,
+        offset: usize,
+        resolve: &dyn Fn(&str) -> Option<ParamType>,
+    ) -> Result<Self, Error> {
+        if let Some(inner) = s.strip_suffix("[]") {
+            let subtype = Self::parse_at(inner, offset, resolve)?;
             return Ok(ParamType::Array(Box::new(subtype)));
         }
-        if s.chars().last() == Some(']') {
-            let num = s.chars()
-                .rev()
-                .skip(1)
-                .take_while(|c| *c != '[')
-                .collect::<String>()
-                .chars()
-                .rev()
-                .collect::<String>();
-            let len = num.parse::<usize>()
-                .map_err(|e| format!("Invalid param type: {}, {:?}", s, e))?;
-            let subtype = Self::from_str(&s[..(s.len() - num.len() - 2)])?;
+        if let Some(inner) = s.strip_suffix(']') {
+            let open = inner.rfind('[').ok_or_else(|| Error::InvalidType {
+                message: format!("Invalid param type: {}", s),
+                position: offset,
+            })?;
+            let len = inner[open + 1..].parse::<usize>().map_err(|e| Error::InvalidType {
+                message: format!("Invalid param type: {}, {:?}", s, e),
+                position: offset + open + 1,
+            })?;
+            let subtype = Self::parse_at(&s[..open], offset, resolve)?;
             return Ok(ParamType::FixedArray(Box::new(subtype), len));
         }
+        if s.starts_with('(') && s.ends_with(')') {
+            let subtypes = split_list(&s[1..(s.len() - 1)])
+                .into_iter()
+                .map(|t| {
+                    // Members are sub-slices of `s`
+                    let member_offset = t.as_ptr() as usize - s.as_ptr() as usize;
+                    Self::parse_at(t.trim(), offset + member_offset, resolve)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(ParamType::Tuple(subtypes));
+        }
+        Self::parse_base(s, resolve).map_err(|e| match e {
+            Error::InvalidType { message, .. } => Error::InvalidType {
+                message,
+                position: offset,
+            },
+            e => e,
+        })
+    }
+
+    /// Parse a type without array suffix or tuple parentheses
+    fn parse_base(s: &str, resolve: &dyn Fn(&str) -> Option<ParamType>) -> Result<Self, Error> {
+        // Solidity source spellings of ABI types
+        if s.contains(char::is_whitespace) {
+            match s.split_whitespace().collect::<Vec<_>>().as_slice() {
+                ["address", "payable"] | ["contract", _] => return Ok(ParamType::Address),
+                ["enum", _] => return Ok(ParamType::Uint(8)),
+                _ => {}
+            }
+        }
 
         Ok(match s {
             "address" => ParamType::Address,
@@ -69,52 +287,136 @@ impl ParamType {
             "string" => ParamType::String,
             "int" => ParamType::Int(256),
             "uint" => ParamType::Uint(256),
-            s if s.starts_with("int") => {
-                let len = s[3..]
-                    .parse::<usize>()
-                    .map_err(|e| format!("Invalid param type: {}, {:?}", s, e))?;
-                if len < 8 || len > 256 || len % 8 != 0 {
-                    return Err(format!("Invalid param type: {}", s));
-                }
-                ParamType::Int(len)
+            "fixed" => ParamType::Fixed(128, 18),
+            "ufixed" => ParamType::Ufixed(128, 18),
+            s if s.starts_with("fixed") => {
+                let (bits, decimals) = parse_fixed_dims(s, &s[5..])?;
+                ParamType::Fixed(bits, decimals)
             }
-            s if s.starts_with("uint") => {
-                let len = s[4..]
-                    .parse::<usize>()
-                    .map_err(|e| format!("Invalid param type: {}, {:?}", s, e))?;
-                if len < 8 || len > 256 || len % 8 != 0 {
-                    return Err(format!("Invalid param type: {}", s));
-                }
-                ParamType::Uint(len)
+            s if s.starts_with("ufixed") => {
+                let (bits, decimals) = parse_fixed_dims(s, &s[6..])?;
+                ParamType::Ufixed(bits, decimals)
             }
+            s if s.starts_with("int") => ParamType::Int(parse_int_width(s, &s[3..])?),
+            s if s.starts_with("uint") => ParamType::Uint(parse_int_width(s, &s[4..])?),
             s if s.starts_with("bytes") => {
-                let len = s[4..]
-                    .parse::<usize>()
-                    .map_err(|e| format!("Invalid param type: {}, {:?}", s, e))?;
-                if len <= 0 || len > 32 {
-                    return Err(format!("Invalid param type: {}", s));
+                let len = parse_type_number(s, &s[5..])?;
+                if len == 0 || len > 32 {
+                    return Err(Error::InvalidType {
+                        message: format!(
+                            "Invalid param type: {}, size must be between 1 and 32, got {}",
+                            s, len
+                        ),
+                        position: 0,
+                    });
                 }
                 ParamType::FixedBytes(len)
             }
-            _ => return Err(format!("Invalid param type: {}", s)),
+            _ => {
+                return resolve(s).ok_or_else(|| Error::InvalidType {
+                    message: format!("Invalid param type: {}", s),
+                    position: 0,
+                })
+            }
         })
     }
 
-    /// Padded value length
-    pub fn value_length(&self, value_str: &str) -> usize {
-        32
+    /// Dynamic array of `t`
+    pub fn array_of(t: ParamType) -> ParamType {
+        ParamType::Array(Box::new(t))
+    }
+
+    /// Fixed size array of `n` elements of `t`
+    pub fn fixed_array_of(t: ParamType, n: usize) -> ParamType {
+        ParamType::FixedArray(Box::new(t), n)
+    }
+
+    /// Tuple of `ts`
+    pub fn tuple_of(ts: Vec<ParamType>) -> ParamType {
+        ParamType::Tuple(ts)
+    }
+
+    /// Tuple of the given component types
+    pub fn tuple_from_components(components: &[ParamType]) -> ParamType {
+        ParamType::Tuple(components.to_vec())
+    }
+
+    /// Parse a JSON ABI `type` field, where `tuple`, `tuple[]` or
+    /// `tuple[N]` stand for the tuple of `components`
+    pub fn from_components(kind: &str, components: &[ParamType]) -> Result<Self, Error> {
+        Self::from_owned_components(kind, components.to_vec())
+    }
+
+    /// `from_components` taking ownership, the tuple is moved into the
+    /// result instead of cloned
+    pub(crate) fn from_owned_components(
+        kind: &str,
+        components: Vec<ParamType>,
+    ) -> Result<Self, Error> {
+        let tuple = Cell::new(Some(ParamType::Tuple(components)));
+        Self::parse_with(kind, &|name| if name == "tuple" { tuple.take() } else { None })
+    }
+
+    /// Encoded size in bytes of a static type, `None` for dynamic types
+    ///
+    /// Agrees with `is_dynamic`: a zero-length fixed array is static with
+    /// size 0 whatever its element type, e.g. `string[0]`.
+    pub fn fixed_size(&self) -> Option<usize> {
+        match self {
+            ParamType::Bytes | ParamType::String | ParamType::Array(_) => None,
+            ParamType::FixedArray(_, 0) => Some(0),
+            ParamType::FixedArray(subtype, len) => subtype.fixed_size().map(|size| size * len),
+            ParamType::Tuple(subtypes) => subtypes.iter().map(|t| t.fixed_size()).sum(),
+            _ => Some(WORD_SIZE),
+        }
+    }
+
+    /// Canonical name annotated with its layout, e.g. `uint256[] (dynamic)`
+    /// or `(uint256,bool) (static, 64 bytes)`
+    pub fn describe(&self) -> String {
+        match self.fixed_size() {
+            Some(size) => format!("{} (static, {} bytes)", self, size),
+            None => format!("{} (dynamic)", self),
+        }
+    }
+
+    /// Padded value length: the inline size of a static type, or the tail
+    /// size of a dynamic type
+    pub fn value_length(&self, value_str: &str) -> Result<usize, Error> {
+        if let Some(size) = self.fixed_size() {
+            return Ok(size);
+        }
+        match self {
+            ParamType::Bytes | ParamType::String => {
+                let (_, value_bytes) = parse_bytes(value_str)?;
+                Ok(WORD_SIZE + value_bytes.len())
+            }
+            ParamType::Array(subtype) => {
+                let values = split_values(value_str, '[', ']')?;
+                Ok(WORD_SIZE + sequence_length(std::iter::repeat(&**subtype).zip(values))?)
+            }
+            ParamType::FixedArray(subtype, _) => {
+                let values = split_values(value_str, '[', ']')?;
+                sequence_length(std::iter::repeat(&**subtype).zip(values))
+            }
+            ParamType::Tuple(subtypes) => {
+                let values = split_values(value_str, '(', ')')?;
+                sequence_length(subtypes.iter().zip(values))
+            }
+            _ => unreachable!("static types have a fixed size"),
+        }
     }
 
     /// Check if this param type can be dynamic
     pub fn maybe_dynamic(&self) -> bool {
-        match self {
+        matches!(
+            self,
             ParamType::Bytes
-            | ParamType::String
-            | ParamType::Array(_)
-            | ParamType::FixedArray(_, _)
-            | ParamType::Tuple(_) => true,
-            _ => false,
-        }
+                | ParamType::String
+                | ParamType::Array(_)
+                | ParamType::FixedArray(_, _)
+                | ParamType::Tuple(_)
+        )
     }
 
     /// Check if the type is dynamic
@@ -122,195 +424,609 @@ impl ParamType {
         match self {
             ParamType::Bytes | ParamType::String | ParamType::Array(_) => true,
             ParamType::FixedArray(subtype, len) if *len > 0 => subtype.is_dynamic(),
-            ParamType::Tuple(subtypes) if subtypes.len() > 0 => {
+            ParamType::Tuple(subtypes) if !subtypes.is_empty() => {
                 subtypes.iter().any(|t| t.is_dynamic())
             }
             _ => false,
         }
     }
+
+    /// Maximum nesting depth: 1 for scalars, 2 for `uint256[]`, 3 for
+    /// `(uint256,(bool,address))`
+    pub fn depth(&self) -> usize {
+        match self {
+            ParamType::Array(subtype) | ParamType::FixedArray(subtype, _) => 1 + subtype.depth(),
+            ParamType::Tuple(subtypes) => 1 + subtypes.iter().map(|t| t.depth()).max().unwrap_or(0),
+            _ => 1,
+        }
+    }
+
+    /// Check if the type is a dynamic or fixed size array
+    pub fn is_array(&self) -> bool {
+        self.array_element().is_some()
+    }
+
+    /// Element type of a dynamic or fixed size array
+    pub fn array_element(&self) -> Option<&ParamType> {
+        match self {
+            ParamType::Array(subtype) | ParamType::FixedArray(subtype, _) => Some(subtype),
+            _ => None,
+        }
+    }
+
+    /// Check if the type is a tuple
+    pub fn is_tuple(&self) -> bool {
+        matches!(self, ParamType::Tuple(_))
+    }
+
+    /// Pre-order traversal of this type and all its sub-types: array and
+    /// fixed array elements, and tuple members
+    pub fn visit<F: FnMut(&ParamType)>(&self, f: &mut F) {
+        f(self);
+        match self {
+            ParamType::Array(subtype) | ParamType::FixedArray(subtype, _) => subtype.visit(f),
+            ParamType::Tuple(subtypes) => subtypes.iter().for_each(|t| t.visit(f)),
+            _ => {}
+        }
+    }
+
+    /// Check if the type takes an offset slot in the head, with its value
+    /// encoded in the tail. This is the only predicate offset planning uses.
+    pub fn needs_offset(&self) -> bool {
+        self.is_dynamic()
+    }
+
+    /// Number of head words a value of this type takes in an enclosing
+    /// tuple or param list: 1 for the offset of a dynamic type, all its
+    /// words for a static type, e.g. 3 for `(uint256,(bool,address))`
+    pub fn encode_type_component_count(&self) -> usize {
+        if self.needs_offset() {
+            return 1;
+        }
+        self.fixed_size().unwrap_or(WORD_SIZE) / WORD_SIZE
+    }
+}
+
+/// Split a comma separated list on its top level commas, commas nested in
+/// brackets, parentheses or double quotes are kept.
+fn split_list(s: &str) -> Vec<&str> {
+    if s.trim().is_empty() {
+        return Vec::new();
+    }
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut quoted = false;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '[' | '(' if !quoted => depth += 1,
+            ']' | ')' if !quoted => depth = depth.saturating_sub(1),
+            ',' if !quoted && depth == 0 => {
+                items.push(s[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(s[start..].trim());
+    items
+}
+
+/// Split an array (`[a,b]`) or tuple (`(a,b)`) value literal into its
+/// element values, surrounding double quotes of an element are removed.
+fn split_values(value_str: &str, open: char, close: char) -> Result<Vec<&str>, Error> {
+    let value_str = value_str.trim();
+    if !(value_str.starts_with(open) && value_str.ends_with(close)) || value_str.len() < 2 {
+        return Err(Error::InvalidValue(format!(
+            "Invalid value={}, expected {}...{}",
+            value_str, open, close
+        )));
+    }
+    Ok(split_list(&value_str[1..(value_str.len() - 1)])
+        .into_iter()
+        .map(|v| {
+            if v.len() >= 2 && v.starts_with('"') && v.ends_with('"') {
+                &v[1..(v.len() - 1)]
+            } else {
+                v
+            }
+        })
+        .collect())
+}
+
+/// Encode a length or offset as a big-endian 32-byte word, the `usize` bytes
+/// fill the low end whatever the platform width
+pub(crate) fn encode_usize(value: usize) -> Bytes {
+    let value_bytes = value.to_be_bytes();
+    let mut buf = vec![0u8; WORD_SIZE];
+    buf[WORD_SIZE - value_bytes.len()..].copy_from_slice(&value_bytes);
+    buf
+}
+
+/// Encode a value in place: the inline content of a static type, or the
+/// tail content of a dynamic type.
+fn encode_value(param_type: &ParamType, value_str: &str) -> Result<Bytes, Error> {
+    match param_type {
+        ParamType::Array(subtype) => {
+            let values = split_values(value_str, '[', ']')?;
+            let mut buf = encode_usize(values.len());
+            buf.extend(encode_sequence(
+                std::iter::repeat(&**subtype).zip(values),
+            )?);
+            Ok(buf)
+        }
+        ParamType::FixedArray(subtype, len) => {
+            let values = split_values(value_str, '[', ']')?;
+            if values.len() != *len {
+                return Err(Error::InvalidLength {
+                    expected: *len,
+                    actual: values.len(),
+                });
+            }
+            encode_sequence(std::iter::repeat(&**subtype).zip(values))
+        }
+        ParamType::Tuple(subtypes) => {
+            let values = split_values(value_str, '(', ')')?;
+            if values.len() != subtypes.len() {
+                return Err(Error::InvalidLength {
+                    expected: subtypes.len(),
+                    actual: values.len(),
+                });
+            }
+            encode_sequence(subtypes.iter().zip(values))
+        }
+        _ => encode_single(param_type, value_str),
+    }
+}
+
+/// Total encoded length of a head/tail encoded sequence
+fn sequence_length<'t, 'v, I>(items: I) -> Result<usize, Error>
+where
+    I: IntoIterator<Item = (&'t ParamType, &'v str)>,
+{
+    items.into_iter().try_fold(0, |total, (param_type, value_str)| {
+        let len = param_type.value_length(value_str)?;
+        Ok(total + if param_type.needs_offset() { WORD_SIZE + len } else { len })
+    })
+}
+
+/// Encode a sequence with the head/tail layout: static values inline in
+/// the head, dynamic values as an offset in the head pointing into the tail.
+fn encode_sequence<'t, 'v, I>(items: I) -> Result<Bytes, Error>
+where
+    I: IntoIterator<Item = (&'t ParamType, &'v str)>,
+{
+    let mut head_words = 0;
+    let mut encoded = Vec::new();
+    for (param_type, value_str) in items {
+        head_words += param_type.encode_type_component_count();
+        encoded.push(if param_type.needs_offset() {
+            (true, encode_value(param_type, value_str)?)
+        } else {
+            (false, encode_inline(param_type, value_str)?)
+        });
+    }
+    Ok(layout_sequence(encoded, head_words))
+}
+
+/// Encode a static value, which must fill exactly its head words
+fn encode_inline(param_type: &ParamType, value_str: &str) -> Result<Bytes, Error> {
+    let value = encode_value(param_type, value_str)?;
+    if value.len() != param_type.encode_type_component_count() * WORD_SIZE {
+        return Err(Error::Unsupported(format!(
+            "Cannot encode type: {}",
+            param_type
+        )));
+    }
+    Ok(value)
+}
+
+/// Lay out encoded `(dynamic, value)` pairs as the head, `head_words` long,
+/// followed by the tail. Shared by the value string and token encoders.
+pub(crate) fn layout_sequence<V: AsRef<[u8]>>(
+    encoded: Vec<(bool, V)>,
+    head_words: usize,
+) -> Bytes {
+    let head_len = head_words * WORD_SIZE;
+    let mut head = Vec::with_capacity(head_len);
+    let mut tail = Vec::new();
+    for (dynamic, value) in encoded {
+        if dynamic {
+            head.extend(encode_usize(head_len + tail.len()));
+            tail.extend_from_slice(value.as_ref());
+        } else {
+            head.extend_from_slice(value.as_ref());
+        }
+    }
+    head.extend(tail);
+    head
 }
 
+#[derive(Debug, Clone)]
 enum ParamItem<'a> {
-    Fixed {
-        param_type: ParamType,
-        value_str: &'a str,
-    },
-    Dynamic {
-        offset: Option<usize>,
-        param_type: ParamType,
-        value_str: &'a str,
-    },
+    /// Typed value string
+    Value(ParamType, &'a str),
+    /// Already encoded static head content
+    Raw(Bytes),
 }
 
 /// Params
+#[derive(Debug, Clone, Default)]
 pub struct Params<'a> {
-    items: Vec<(ParamType, &'a str)>,
+    items: Vec<ParamItem<'a>>,
 }
 
 impl<'a> Params<'a> {
-    /// Encode all params
-    pub fn encode(&mut self) -> Result<Bytes, String> {
-        let mut total_offset: usize = 0;
-        let mut items: Vec<ParamItem> = self.items
+    /// Create an empty param list
+    pub fn new() -> Self {
+        Params { items: Vec::new() }
+    }
+
+    /// Append a param
+    pub fn push(&mut self, param_type: ParamType, value_str: &'a str) {
+        self.items.push(ParamItem::Value(param_type, value_str));
+    }
+
+    /// Append already encoded bytes, e.g. from another tool, as static head
+    /// content. The length must be a multiple of 32. Raw bytes are not part
+    /// of the `encode_with_selector` signature.
+    pub fn push_raw(&mut self, bytes: Vec<u8>) {
+        debug_assert!(
+            pad_to_word(bytes.len()) == bytes.len(),
+            "raw param length must be a multiple of 32"
+        );
+        self.items.push(ParamItem::Raw(bytes));
+    }
+
+    fn values(&self) -> impl Iterator<Item = (&ParamType, &'a str)> {
+        self.items.iter().filter_map(|item| match item {
+            ParamItem::Value(param_type, value_str) => Some((param_type, *value_str)),
+            ParamItem::Raw(_) => None,
+        })
+    }
+
+    /// Total byte length `encode` will produce, computed without encoding
+    pub fn encoded_size(&self) -> Result<usize, Error> {
+        let raw_len: usize = self
+            .items
             .iter()
-            .map(|(param_type, value_str)| match param_type.maybe_dynamic() {
-                true => {
-                    total_offset += 32;
-                    ParamItem::Dynamic {
-                        offset: None,
-                        param_type: param_type.clone(),
-                        value_str: value_str,
-                    }
+            .map(|item| match item {
+                ParamItem::Raw(bytes) => bytes.len(),
+                ParamItem::Value(_, _) => 0,
+            })
+            .sum();
+        Ok(raw_len + sequence_length(self.values())?)
+    }
+
+    /// Check every value against its type before any encoding, the error
+    /// names the index and type of the first failing param
+    pub fn validate(&self) -> Result<(), Error> {
+        for (i, item) in self.items.iter().enumerate() {
+            if let ParamItem::Value(param_type, value_str) = item {
+                tokenize(param_type, value_str)
+                    .map_err(|e| e.context(format!("in param {} of type {}", i, param_type)))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Encode all params, identical dynamic values are encoded only once
+    ///
+    /// Errors name the index and type of the failing param, like `validate`.
+    pub fn encode(&mut self) -> Result<Bytes, Error> {
+        let mut tails: HashMap<(&ParamType, &str), Bytes> = HashMap::new();
+        let mut inline = Vec::new();
+        let mut head_words = 0;
+        for (i, item) in self.items.iter().enumerate() {
+            let (param_type, value_str) = match item {
+                ParamItem::Value(param_type, value_str) => (param_type, *value_str),
+                ParamItem::Raw(bytes) => {
+                    head_words += bytes.len() / WORD_SIZE;
+                    continue;
                 }
-                false => {
-                    total_offset += param_type.value_length(value_str);
-                    ParamItem::Fixed {
-                        param_type: param_type.clone(),
-                        value_str: value_str,
-                    }
+            };
+            let context = |e: Error| e.context(format!("in param {} of type {}", i, param_type));
+            head_words += param_type.encode_type_component_count();
+            if !param_type.needs_offset() {
+                inline.push(encode_inline(param_type, value_str).map_err(context)?);
+            } else if let Entry::Vacant(entry) = tails.entry((param_type, value_str)) {
+                entry.insert(encode_value(param_type, value_str).map_err(context)?);
+            }
+        }
+        // Lay out borrowed slices so raw bytes and repeated tails are not copied twice
+        let mut inline = inline.iter();
+        let encoded: Vec<(bool, &[u8])> = self
+            .items
+            .iter()
+            .map(|item| match item {
+                ParamItem::Raw(bytes) => (false, bytes.as_slice()),
+                ParamItem::Value(param_type, _) if !param_type.needs_offset() => {
+                    (false, inline.next().expect("inline value encoded").as_slice())
+                }
+                ParamItem::Value(param_type, value_str) => {
+                    (true, tails[&(param_type, *value_str)].as_slice())
                 }
             })
             .collect();
+        Ok(layout_sequence(encoded, head_words))
+    }
 
-        let mut buf: Vec<u8> = Vec::new();
-        while !items.is_empty() {
-            let mut next_items: Vec<ParamItem> = Vec::new();
-            items.iter_mut().for_each(|item| match item {
-                ParamItem::Dynamic {
-                    ref mut offset,
-                    param_type,
-                    value_str,
-                } => {
-                    *offset = Some(total_offset);
-                    total_offset += 32 + param_type.value_length(value_str);
-                }
-                _ => {}
-            });
-            items = next_items;
-        }
+    /// Encode a call of function `name`: the selector computed from the
+    /// param types followed by all encoded params
+    pub fn encode_with_selector(&mut self, name: &str) -> Result<Bytes, Error> {
+        let mut buf = signature::selector_of(name, self.values().map(|(t, _)| t)).to_vec();
+        buf.extend(self.encode()?);
         Ok(buf)
     }
 }
 
-fn parse_bytes(value_str: &str) -> (usize, Bytes) {
-    let mut value_bytes = if value_str.starts_with("0x") {
-        value_str[2..].from_hex().unwrap()
-    } else {
-        value_str.as_bytes().to_vec()
+/// Encode params with the standard head/tail layout
+pub fn encode_params(params: &[(ParamType, &str)]) -> Result<Bytes, Error> {
+    #[cfg(feature = "tracing")]
+    let _span = debug_span!(
+        "encode_params",
+        types = %signature::signature_of("", params.iter().map(|(t, _)| t))
+    )
+    .entered();
+    let mut items = Params::new();
+    for (param_type, value_str) in params {
+        items.push(param_type.clone(), value_str);
+    }
+    let encoded = items.encode();
+    #[cfg(feature = "tracing")]
+    match &encoded {
+        Ok(encoded) => trace!(len = encoded.len(), "encoded"),
+        Err(e) => debug!(error = %e, "encode failed"),
+    }
+    encoded
+}
+
+/// Encode params with the standard head/tail layout straight into `w`
+///
+/// Dynamic offsets are computed from the value lengths upfront, then the
+/// head and each tail value are written without building the whole
+/// encoding. On error a prefix of the encoding may already be written.
+pub fn encode_params_to<W: Write>(w: &mut W, params: &[(ParamType, &str)]) -> Result<(), Error> {
+    let mut write = |bytes: &[u8]| {
+        w.write_all(bytes)
+            .map_err(|e| Error::Io(format!("Write encoded data failed: {}", e)))
     };
-    let len = value_bytes.len();
-    if value_bytes.len() % 32 > 0 {
-        let padding_len = 32 - (value_bytes.len() % 32);
-        value_bytes.extend(std::iter::repeat(0u8).take(padding_len).collect::<Vec<_>>());
+    let head_words: usize = params
+        .iter()
+        .map(|(param_type, _)| param_type.encode_type_component_count())
+        .sum();
+    let mut offset = head_words * WORD_SIZE;
+    for (param_type, value_str) in params {
+        if param_type.needs_offset() {
+            write(&encode_usize(offset))?;
+            offset += param_type.value_length(value_str)?;
+        } else {
+            write(&encode_inline(param_type, value_str)?)?;
+        }
+    }
+    for (param_type, value_str) in params {
+        if param_type.needs_offset() {
+            write(&encode_value(param_type, value_str)?)?;
+        }
     }
-    (len, value_bytes)
+    Ok(())
 }
 
-/// Encode a single value by type
-pub fn encode_single(param_type: &ParamType, value_str: &str) -> Result<Bytes, String> {
+/// Encode params with the standard head/tail layout, value strings are
+/// turned into tokens by `tokenizer`
+///
+/// The returned tokens are checked against the param types with
+/// `type_check`, so a tokenizer producing the wrong shape fails with an
+/// `Error` instead of a broken layout.
+pub fn encode_params_with(
+    params: &[(ParamType, &str)],
+    tokenizer: &dyn Tokenizer,
+) -> Result<Bytes, Error> {
+    let tokens = params
+        .iter()
+        .map(|(param_type, value_str)| tokenizer.tokenize(param_type, value_str))
+        .collect::<Result<Vec<_>, _>>()?;
+    let types: Vec<ParamType> = params.iter().map(|(param_type, _)| param_type.clone()).collect();
+    token::encode_tokens(&types, &tokens)
+}
+
+/// Keccak-256 of the standard encoding of params, the same as Solidity's
+/// `keccak256(abi.encode(...))`
+pub fn encode_hash(params: &[(ParamType, &str)]) -> Result<[u8; 32], Error> {
+    Ok(tiny_keccak::keccak256(&encode_params(params)?))
+}
+
+/// Encode params like Solidity's `abi.encodePacked`
+///
+/// Scalars take their packed size without padding, e.g. one byte for
+/// `bool` and 20 for `address`, and `bytes` and `string` are their raw
+/// content without a length. Array elements are still padded to one word
+/// each. Tuples and arrays of dynamic or nested types are rejected with
+/// `Error::Unsupported`, as Solidity does.
+pub fn encode_packed(params: &[(ParamType, &str)]) -> Result<Bytes, Error> {
+    let mut buf = Vec::new();
+    for (param_type, value_str) in params {
+        encode_packed_value(&mut buf, param_type, value_str)?;
+    }
+    Ok(buf)
+}
+
+fn encode_packed_value(
+    buf: &mut Bytes,
+    param_type: &ParamType,
+    value_str: &str,
+) -> Result<(), Error> {
+    let unsupported = || {
+        Error::Unsupported(format!(
+            "Cannot encode packed {}: only scalars, bytes, string and arrays of scalars are supported",
+            param_type
+        ))
+    };
     match param_type {
-        ParamType::Address => {
-            let value_bytes = if value_str.starts_with("0x") {
-                &value_str[2..]
-            } else {
-                &value_str[..]
-            };
-            encode_single(&ParamType::Uint(160), value_bytes)
-        }
-        ParamType::Uint(m) | ParamType::Int(m) => {
-            let mut negative = false;
-            let value = if value_str.starts_with("0x") {
-                U256::from(value_str[2..].from_hex().unwrap().as_slice())
-            } else if value_str.starts_with("-") {
-                match param_type {
-                    ParamType::Uint(_) => {
-                        return Err(format!(
-                            "Invalid value={} for type={:?}",
-                            value_str, param_type
-                        ));
-                    }
-                    _ => {}
-                }
-                negative = true;
-                U256::from_dec_str(&value_str[1..]).unwrap()
-            } else {
-                U256::from_dec_str(value_str).unwrap()
-            };
-            if *m < 256 && value >= U256::from(2).pow(U256::from(*m)) {
-                return Err(format!(
-                    "Overflow value={}, type={:?}",
-                    value_str, param_type
-                ));
+        ParamType::Bytes | ParamType::String => match tokenize(param_type, value_str)? {
+            Token::Bytes(value_bytes) => buf.extend(value_bytes),
+            Token::String(value) => buf.extend(value.into_bytes()),
+            _ => unreachable!("tokenize returns a token of the requested type"),
+        },
+        ParamType::Array(subtype) | ParamType::FixedArray(subtype, _) => {
+            if decode::packed_size(subtype).is_none() {
+                return Err(unsupported());
             }
-            let value = if negative {
-                (!value) + U256::one()
-            } else {
-                value
-            };
-            let mut buf = [0u8; 32];
-            value.to_big_endian(&mut buf);
-            Ok(buf.to_vec())
-        }
-        ParamType::Bool => {
-            let value_str = match value_str {
-                "true" => "1",
-                "false" => "0",
-                _ => return Err(format!("Invalid value for bool: {}", value_str)),
-            };
-            Ok(encode_single(&ParamType::Uint(8), value_str)?)
-        }
-        ParamType::Fixed(m, n) => {
-            Ok(vec![])
-        }
-        ParamType::Ufixed(m, n) => {
-            Ok(vec![])
-        }
-        ParamType::FixedBytes(m) => {
-            let (len, value_bytes) = parse_bytes(value_str);
-            if len > *m {
-                Err(format!("Error value length: value={}", value_str))
-            } else {
-                Ok(value_bytes)
+            let values = split_values(value_str, '[', ']')?;
+            if let ParamType::FixedArray(_, len) = param_type {
+                if values.len() != *len {
+                    return Err(Error::InvalidLength {
+                        expected: *len,
+                        actual: values.len(),
+                    });
+                }
             }
-        }
-        ParamType::Bytes => {
-            let mut buf: Vec<u8> = Vec::new();
-            let (len, value_bytes) = parse_bytes(value_str);
-            if len > value_str.chars().count() {
-                Err(format!("Value is not bytes: {}", value_str))
-            } else {
-                // TODO: ugly
-                let len_string = format!("{}", len);
-                buf.extend(encode_single(&ParamType::Uint(256), len_string.as_str()).unwrap());
-                buf.extend(value_bytes);
-                Ok(buf)
+            for value in values {
+                buf.extend(encode_single(subtype, value)?);
             }
         }
-        ParamType::String => {
-            let mut buf: Vec<u8> = Vec::new();
-            let (len, value_bytes) = parse_bytes(value_str);
-            // TODO: ugly
-            let len_string = format!("{}", len);
-            buf.extend(encode_single(&ParamType::Uint(256), len_string.as_str()).unwrap());
-            buf.extend(value_bytes);
-            Ok(buf)
-        }
-        // ==== Dynamic Types ====
+        ParamType::FixedBytes(size) => buf.extend(&encode_single(param_type, value_str)?[..*size]),
         _ => {
-            Err(format!("Cannot encode single dynamic type: {:?}", param_type))
+            let size = decode::packed_size(param_type).ok_or_else(unsupported)?;
+            buf.extend(&encode_single(param_type, value_str)?[WORD_SIZE - size..]);
+        }
+    }
+    Ok(())
+}
+
+/// Encode params with the standard head/tail layout and the given options,
+/// through the token encoder which applies them to each value
+pub fn encode_params_with_options(
+    params: &[(ParamType, &str)],
+    options: &EncodeOptions,
+) -> Result<Bytes, Error> {
+    let tokens = params
+        .iter()
+        .map(|(param_type, value_str)| tokenize(param_type, value_str))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(token::encode_token_sequence_with(&tokens, options))
+}
+
+/// Encode a single value of any type as a one element param list, the same
+/// as Solidity's `abi.encode(value)`. Dynamic values are preceded by their
+/// offset word, unlike `encode_single` which returns the bare tail content.
+pub fn encode(param_type: &ParamType, value_str: &str) -> Result<Bytes, Error> {
+    encode_sequence(std::iter::once((param_type, value_str)))
+}
+
+/// Encode a function call: the 4-byte selector followed by the encoded
+/// params, just the selector for a function without params
+pub fn encode_function_call(name: &str, params: &[(ParamType, &str)]) -> Result<Bytes, Error> {
+    let mut buf = signature::selector_of(name, params.iter().map(|(t, _)| t)).to_vec();
+    buf.extend(encode_params(params)?);
+    Ok(buf)
+}
+
+/// Encode a call from a signature like `transfer(address,uint256)` and one
+/// value per input
+///
+/// The signature is parsed and canonicalized first, so `uint` and
+/// whitespace are accepted. A wrong number of values is
+/// `Error::InvalidLength`.
+pub fn encode_input(signature: &str, values: &[&str]) -> Result<Bytes, Error> {
+    let function = parse_function(signature)?;
+    if function.inputs.len() != values.len() {
+        return Err(Error::InvalidLength {
+            expected: function.inputs.len(),
+            actual: values.len(),
+        });
+    }
+    let params: Vec<(ParamType, &str)> = function
+        .inputs
+        .into_iter()
+        .map(|p| Arc::try_unwrap(p.kind).unwrap_or_else(|kind| (*kind).clone()))
+        .zip(values.iter().cloned())
+        .collect();
+    encode_function_call(&function.name, &params)
+}
+
+/// Deployment data: `bytecode` followed by the encoded constructor args
+///
+/// A wrong number of values is `Error::InvalidLength`.
+pub fn encode_constructor(
+    bytecode: &[u8],
+    types: &[ParamType],
+    values: &[&str],
+) -> Result<Bytes, Error> {
+    if types.len() != values.len() {
+        return Err(Error::InvalidLength {
+            expected: types.len(),
+            actual: values.len(),
+        });
+    }
+    let mut params = Params::new();
+    for (param_type, value_str) in types.iter().zip(values) {
+        params.push(param_type.clone(), value_str);
+    }
+    let mut buf = bytecode.to_vec();
+    buf.extend(params.encode()?);
+    Ok(buf)
+}
+
+/// Gas per zero calldata byte
+const CALLDATA_ZERO_BYTE_GAS: u64 = 4;
+/// Gas per non-zero calldata byte, since EIP-2028
+const CALLDATA_NONZERO_BYTE_GAS: u64 = 16;
+
+/// Gas charged for `data` as transaction calldata, excluding the base
+/// transaction cost
+pub fn calldata_gas(data: &[u8]) -> u64 {
+    data.iter()
+        .map(|b| if *b == 0 { CALLDATA_ZERO_BYTE_GAS } else { CALLDATA_NONZERO_BYTE_GAS })
+        .sum()
+}
+
+/// Encode dynamic `bytes` and check the payload is exactly `expected_len` bytes
+pub fn encode_bytes_exact(value_str: &str, expected_len: usize) -> Result<Bytes, Error> {
+    let (len, _) = parse_bytes(value_str)?;
+    if len != expected_len {
+        return Err(Error::InvalidLength {
+            expected: expected_len,
+            actual: len,
+        });
+    }
+    encode_single(&ParamType::Bytes, value_str)
+}
+
+/// Encode a big-endian unsigned integer of at most `bits` bits, left padded
+/// to one word
+pub fn encode_uint_bytes(be: &[u8], bits: usize) -> Result<Bytes, Error> {
+    check_int_width(&format!("uint{}", bits), bits)?;
+    let start = be.iter().position(|b| *b != 0).unwrap_or(be.len());
+    let value_bytes = &be[start..];
+    if value_bytes.len() * 8 > bits {
+        return Err(Error::Overflow(format!(
+            "Overflow value=0x{}, type={:?}",
+            value_bytes.to_hex(),
+            ParamType::Uint(bits)
+        )));
+    }
+    let mut buf = [0u8; WORD_SIZE];
+    buf[(WORD_SIZE - value_bytes.len())..].copy_from_slice(value_bytes);
+    Ok(buf.to_vec())
+}
+
+/// Encode a single value by type
+///
+/// Integers are decimal, `0x` hex, or a decimal amount followed by a unit
+/// (`wei`, `gwei` or `ether`), e.g. `1.5 ether`. A bare `0x` and an empty
+/// value are zero.
+///
+/// Arrays and tuples are encoded as their bare content, e.g. the length
+/// word followed by the elements for `uint256[]`, without the leading offset
+/// word `encode` adds for dynamic values.
+pub fn encode_single(param_type: &ParamType, value_str: &str) -> Result<Bytes, Error> {
+    match param_type {
+        ParamType::Array(_) | ParamType::FixedArray(_, _) | ParamType::Tuple(_) => {
+            encode_value(param_type, value_str)
         }
-        // ParamType::Array(subtype) => {
-        //     // TODO: dynamic
-        //     Ok(vec![])
-        // }
-        // ParamType::FixedArray(subtype, m) => {
-        //     // TODO: maybe dynamic
-        //     Ok(vec![])
-        // }
-        // ParamType::Tuple(subtypes) => {
-        //     // TODO: maybe dynamic
-        //     Ok(vec![])
-        // },
+        _ => Ok(token::encode_token(&tokenize_scalar(param_type, value_str)?)),
     }
 }
 
@@ -318,6 +1034,7 @@ pub fn encode_single(param_type: &ParamType, value_str: &str) -> Result<Bytes, S
 mod tests {
 
     use super::*;
+    use hex::FromHex;
 
     #[test]
     fn test_parse_param_type() {
@@ -343,37 +1060,471 @@ mod tests {
         );
         assert_eq!(ParamType::from_str("uint"), Ok(ParamType::Uint(256)));
         assert_eq!(ParamType::from_str("uint128"), Ok(ParamType::Uint(128)));
+        assert_eq!(ParamType::from_str("bytes1"), Ok(ParamType::FixedBytes(1)));
+        assert_eq!(ParamType::from_str("bytes32"), Ok(ParamType::FixedBytes(32)));
         assert_eq!(
             ParamType::from_str("string[]"),
             Ok(ParamType::Array(Box::new(ParamType::String)))
         );
+        assert_eq!(
+            ParamType::from_str("(uint256,(bool,address))[]"),
+            Ok(ParamType::Array(Box::new(ParamType::Tuple(vec![
+                ParamType::Uint(256),
+                ParamType::Tuple(vec![ParamType::Bool, ParamType::Address]),
+            ]))))
+        );
     }
 
     #[test]
-    fn test_encode_single_int() {
-        let expected = "0000000000000000000000000000000000000000000000000000000000000003"
-            .from_hex()
-            .unwrap();
-        let param_type = ParamType::from_str("uint").unwrap();
-        assert_eq!(encode_single(&param_type, "3").unwrap(), expected);
-        assert_eq!(encode_single(&param_type, "0x03").unwrap(), expected);
+    fn test_parse_multi_dimensional_array() {
+        let uint = || Box::new(ParamType::Uint(256));
+        let param_type = ParamType::from_str("uint256[5][2]").unwrap();
+        assert_eq!(
+            param_type,
+            ParamType::FixedArray(Box::new(ParamType::FixedArray(uint(), 5)), 2)
+        );
+        assert!(!param_type.is_dynamic());
+        assert_eq!(param_type.fixed_size(), Some(10 * 32));
+        let encoded = encode(&param_type, "[[1,2,3,4,5],[6,7,8,9,10]]").unwrap();
+        assert_eq!(encoded.len(), 10 * 32);
+        assert_eq!(encoded[5 * 32 + 31], 6);
 
-        let expected = "fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffeb3"
-            .from_hex()
-            .unwrap();
-        let param_type = ParamType::from_str("int").unwrap();
-        assert_eq!(encode_single(&param_type, "-333").unwrap(), expected);
+        let param_type = ParamType::from_str("uint256[][2]").unwrap();
         assert_eq!(
-            encode_single(
-                &param_type,
-                "0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffeb3"
-            ).unwrap(),
-            expected
+            param_type,
+            ParamType::FixedArray(Box::new(ParamType::Array(uint())), 2)
         );
+        assert!(param_type.is_dynamic());
+
+        let param_type = ParamType::from_str("uint256[2][]").unwrap();
+        assert_eq!(
+            param_type,
+            ParamType::Array(Box::new(ParamType::FixedArray(uint(), 2)))
+        );
+        assert!(param_type.is_dynamic());
+        assert!(!param_type.array_element().unwrap().is_dynamic());
     }
 
     #[test]
-    fn test_encode_single_bool() {
+    fn test_needs_offset() {
+        assert!(ParamType::from_str("string[2]").unwrap().needs_offset());
+        assert!(ParamType::from_str("uint256[]").unwrap().needs_offset());
+        assert!(!ParamType::from_str("uint256[2]").unwrap().needs_offset());
+        assert!(!ParamType::from_str("uint256").unwrap().needs_offset());
+    }
+
+    #[test]
+    fn test_parse_param_type_synonyms() {
+        assert_eq!(ParamType::from_str("address payable"), Ok(ParamType::Address));
+        assert_eq!(ParamType::from_str("contract IERC20"), Ok(ParamType::Address));
+        assert_eq!(ParamType::from_str("enum Status"), Ok(ParamType::Uint(8)));
+        assert_eq!(
+            ParamType::from_str("(address payable,uint256)[]"),
+            Ok(ParamType::Array(Box::new(ParamType::Tuple(vec![
+                ParamType::Address,
+                ParamType::Uint(256),
+            ]))))
+        );
+        assert!(ParamType::from_str("Status").is_err());
+
+        let resolve = |name: &str| match name {
+            "Status" => Some(ParamType::Uint(8)),
+            _ => None,
+        };
+        assert_eq!(
+            ParamType::parse_with("Status[2]", &resolve),
+            Ok(ParamType::FixedArray(Box::new(ParamType::Uint(8)), 2))
+        );
+        assert!(ParamType::parse_with("Order", &resolve).is_err());
+    }
+
+    #[test]
+    fn test_parse_param_type_tuple_whitespace() {
+        assert_eq!(
+            ParamType::from_str("( uint256 , ( address , bool ) )"),
+            Ok(ParamType::Tuple(vec![
+                ParamType::Uint(256),
+                ParamType::Tuple(vec![ParamType::Address, ParamType::Bool]),
+            ]))
+        );
+        assert_eq!(
+            ParamType::from_str("( address payable , uint8 )[]"),
+            ParamType::from_str("(address,uint8)[]")
+        );
+    }
+
+    #[test]
+    fn test_parse_param_type_bounds() {
+        let width_message = |s: &str, got: usize| {
+            Err(Error::InvalidType {
+                message: format!(
+                    "Invalid param type: {}, width must be between 8 and 256 and a multiple of 8, got {}",
+                    s, got
+                ),
+                position: 0,
+            })
+        };
+        assert_eq!(ParamType::from_str("uint7"), width_message("uint7", 7));
+        assert_eq!(ParamType::from_str("uint512"), width_message("uint512", 512));
+        assert_eq!(ParamType::from_str("int0"), width_message("int0", 0));
+        assert_eq!(
+            ParamType::from_str("bytes33"),
+            Err(Error::InvalidType {
+                message: "Invalid param type: bytes33, size must be between 1 and 32, got 33"
+                    .to_owned(),
+                position: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_param_type_error_position() {
+        let position = |s: &str| match ParamType::from_str(s) {
+            Err(Error::InvalidType { position, .. }) => position,
+            other => panic!("unexpected result: {:?}", other),
+        };
+        assert_eq!(position("uint7"), 0);
+        assert_eq!(position("(uint256,(bool,uint7))"), 15);
+        assert_eq!(position("(uint256, ( bool , uint7 ))[]"), 19);
+        assert_eq!(position("(uint256,bool[x])[2]"), 14);
+        assert_eq!(position("(uint256,5])"), 10);
+        assert_eq!(
+            ParamType::from_str("(uint256,(bool,uint7))")
+                .unwrap_err()
+                .to_string(),
+            "Invalid param type: uint7, width must be between 8 and 256 and a multiple of 8, got 7"
+        );
+        match ::parse_function("f(uint256,strin)") {
+            Err(Error::InvalidType { position, .. }) => assert_eq!(position, 10),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_param_type_unbalanced_brackets() {
+        let error = |s: &str| match ParamType::from_str(s) {
+            Err(Error::InvalidType { message, position }) => (message, position),
+            other => panic!("unexpected result: {:?}", other),
+        };
+        assert_eq!(
+            error("uint256["),
+            ("Invalid param type: uint256[, unclosed '['".to_owned(), 7)
+        );
+        assert_eq!(
+            error("uint256]"),
+            ("Invalid param type: uint256], unbalanced ']'".to_owned(), 7)
+        );
+        assert_eq!(
+            error("uint256[]]"),
+            ("Invalid param type: uint256[]], unbalanced ']'".to_owned(), 9)
+        );
+        assert_eq!(error("(uint256[)]").1, 9);
+        assert_eq!(error("(bool,(uint8)").1, 0);
+    }
+
+    #[test]
+    fn test_depth() {
+        assert_eq!(ParamType::from_str("uint256").unwrap().depth(), 1);
+        assert_eq!(ParamType::from_str("uint256[]").unwrap().depth(), 2);
+        assert_eq!(ParamType::from_str("uint256[][3]").unwrap().depth(), 3);
+        assert_eq!(ParamType::from_str("(uint256,(bool,address))").unwrap().depth(), 3);
+        assert_eq!(ParamType::Tuple(vec![]).depth(), 1);
+    }
+
+    #[test]
+    fn test_type_constructors() {
+        let param_type = ParamType::fixed_array_of(ParamType::array_of(ParamType::Uint(256)), 3);
+        assert_eq!(param_type, ParamType::from_str("uint256[][3]").unwrap());
+        assert_eq!(param_type.to_string(), "uint256[][3]");
+        assert_eq!(
+            ParamType::tuple_of(vec![ParamType::Bool, param_type]),
+            ParamType::from_str("(bool,uint256[][3])").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_array_and_tuple_predicates() {
+        let uint = ParamType::Uint(256);
+        let array = ParamType::from_str("uint256[]").unwrap();
+        assert!(array.is_array());
+        assert!(!array.is_tuple());
+        assert_eq!(array.array_element(), Some(&uint));
+
+        let fixed_array = ParamType::from_str("uint256[3]").unwrap();
+        assert!(fixed_array.is_array());
+        assert_eq!(fixed_array.array_element(), Some(&uint));
+
+        let tuple = ParamType::from_str("(uint256)").unwrap();
+        assert!(tuple.is_tuple());
+        assert!(!tuple.is_array());
+        assert_eq!(tuple.array_element(), None);
+        assert!(!uint.is_array() && !uint.is_tuple());
+    }
+
+    #[test]
+    fn test_visit() {
+        let param_type = ParamType::from_str("(address,(uint256,address[])[2],bool)").unwrap();
+        let mut addresses = 0;
+        param_type.visit(&mut |t| {
+            if *t == ParamType::Address {
+                addresses += 1;
+            }
+        });
+        assert_eq!(addresses, 2);
+
+        let mut order = Vec::new();
+        ParamType::from_str("(uint8,bool[])")
+            .unwrap()
+            .visit(&mut |t| order.push(t.to_string()));
+        assert_eq!(order, vec!["(uint8,bool[])", "uint8", "bool[]", "bool"]);
+    }
+
+    #[test]
+    fn test_try_from_param_type() {
+        let expected = ParamType::Array(Box::new(ParamType::Uint(256)));
+        assert_eq!(ParamType::try_from("uint256[]"), Ok(expected.clone()));
+        assert_eq!(ParamType::try_from("uint256[]".to_owned()), Ok(expected));
+        assert!(ParamType::try_from("uint7").is_err());
+    }
+
+    #[test]
+    fn test_display_param_type() {
+        for type_str in &[
+            "address",
+            "bool",
+            "bytes",
+            "string",
+            "int8",
+            "uint256",
+            "bytes32",
+            "uint256[]",
+            "address[3]",
+            "string[][2]",
+        ] {
+            assert_eq!(ParamType::from_str(type_str).unwrap().to_string(), *type_str);
+        }
+        assert_eq!(ParamType::from_str("uint").unwrap().to_string(), "uint256");
+        assert_eq!(
+            ParamType::Tuple(vec![ParamType::Uint(256), ParamType::Bool]).to_string(),
+            "(uint256,bool)"
+        );
+    }
+
+    #[test]
+    fn test_fixed_param_type_roundtrip() {
+        for param_type in &[
+            ParamType::Fixed(128, 18),
+            ParamType::Ufixed(128, 18),
+            ParamType::Fixed(8, 1),
+            ParamType::Ufixed(256, 80),
+            ParamType::Fixed(64, 10),
+        ] {
+            assert_eq!(ParamType::from_str(&param_type.to_string()).as_ref(), Ok(param_type));
+        }
+        assert_eq!(ParamType::from_str("fixed"), Ok(ParamType::Fixed(128, 18)));
+        assert_eq!(ParamType::from_str("ufixed"), Ok(ParamType::Ufixed(128, 18)));
+        assert_eq!(ParamType::from_str("ufixed[2]").unwrap().to_string(), "ufixed128x18[2]");
+        for type_str in &["fixed128", "fixed7x18", "ufixed128x0", "ufixed128x81", "fixedx18"] {
+            assert!(ParamType::from_str(type_str).is_err(), "{}", type_str);
+        }
+    }
+
+    #[test]
+    fn test_encode_single_int() {
+        let expected = "0000000000000000000000000000000000000000000000000000000000000003"
+            .from_hex()
+            .unwrap();
+        let param_type = ParamType::from_str("uint").unwrap();
+        assert_eq!(encode_single(&param_type, "3").unwrap(), expected);
+        assert_eq!(encode_single(&param_type, "0x03").unwrap(), expected);
+
+        let expected = "fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffeb3"
+            .from_hex()
+            .unwrap();
+        let param_type = ParamType::from_str("int").unwrap();
+        assert_eq!(encode_single(&param_type, "-333").unwrap(), expected);
+        assert_eq!(
+            encode_single(
+                &param_type,
+                "0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffeb3"
+            ).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_encode_single_int_bounds() {
+        fn assert_overflow(param_type: &ParamType, value_str: &str) {
+            match encode_single(param_type, value_str) {
+                Err(Error::Overflow(_)) => {}
+                other => panic!("{} {}: unexpected result {:?}", param_type, value_str, other),
+            }
+        }
+        for bits in [8, 16, 64, 128, 256].iter().cloned() {
+            let bytes = bits / 8;
+            let max = if bits == 256 {
+                U256::max_value()
+            } else {
+                (U256::one() << bits) - U256::one()
+            };
+            let param_type = ParamType::Uint(bits);
+            assert_eq!(
+                encode_single(&param_type, &max.to_string()).unwrap().to_hex(),
+                format!("{}{}", "00".repeat(32 - bytes), "ff".repeat(bytes))
+            );
+            let above_max = if bits == 256 {
+                "115792089237316195423570985008687907853269984665640564039457584007913129639936"
+                    .to_owned()
+            } else {
+                (max + U256::one()).to_string()
+            };
+            assert_overflow(&param_type, &above_max);
+
+            let limit = U256::one() << (bits - 1);
+            let param_type = ParamType::Int(bits);
+            assert_eq!(
+                encode_single(&param_type, &(limit - U256::one()).to_string()).unwrap().to_hex(),
+                format!("{}7f{}", "00".repeat(32 - bytes), "ff".repeat(bytes - 1))
+            );
+            assert_eq!(
+                encode_single(&param_type, &format!("-{}", limit)).unwrap().to_hex(),
+                format!("{}80{}", "ff".repeat(32 - bytes), "00".repeat(bytes - 1))
+            );
+            assert_overflow(&param_type, &limit.to_string());
+            assert_overflow(&param_type, &format!("-{}", limit + U256::one()));
+        }
+        assert_eq!(
+            encode_single(&ParamType::Int(8), "0xff"),
+            encode_single(&ParamType::Int(8), "-1")
+        );
+        assert_overflow(&ParamType::Int(8), "0x0100");
+    }
+
+    #[test]
+    fn test_encode_single_empty_hex_int() {
+        assert_eq!(encode_single(&ParamType::Uint(256), "0x"), Ok(vec![0u8; 32]));
+        assert_eq!(encode_single(&ParamType::Uint(8), "0x"), Ok(vec![0u8; 32]));
+        assert_eq!(encode_single(&ParamType::Int(256), "0x"), Ok(vec![0u8; 32]));
+        assert_eq!(
+            encode_single(&ParamType::Uint(256), "0x"),
+            encode_single(&ParamType::Uint(256), "0")
+        );
+        assert_eq!(encode_single(&ParamType::Uint(256), ""), Ok(vec![0u8; 32]));
+    }
+
+    #[test]
+    fn test_encode_single_units() {
+        assert_eq!(
+            encode_single(&ParamType::Uint(256), "1.5 ether"),
+            encode_single(&ParamType::Uint(256), "1500000000000000000")
+        );
+        assert_eq!(
+            encode_single(&ParamType::Int(64), "-2 gwei"),
+            encode_single(&ParamType::Int(64), "-2000000000")
+        );
+        assert!(encode_single(&ParamType::Uint(256), "0.1 wei").is_err());
+        match encode_single(&ParamType::Uint(32), "1 ether") {
+            Err(Error::Overflow(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_encode_single_trims_whitespace() {
+        assert_eq!(
+            encode_single(&ParamType::Uint(256), " 42 "),
+            encode_single(&ParamType::Uint(256), "42")
+        );
+        assert_eq!(
+            encode_single(&ParamType::Int(8), "\t-1\n"),
+            encode_single(&ParamType::Int(8), "-1")
+        );
+        assert_eq!(
+            encode_single(&ParamType::Bool, " true "),
+            encode_single(&ParamType::Bool, "true")
+        );
+        assert!(encode_single(&ParamType::Uint(256), "4 2").is_err());
+    }
+
+    #[test]
+    fn test_encode_single_fixed() {
+        assert_eq!(
+            encode_single(&ParamType::Fixed(128, 3), "12.345"),
+            encode_single(&ParamType::Int(128), "12345")
+        );
+        assert_eq!(
+            encode_single(&ParamType::Fixed(128, 18), "-0.25").unwrap().to_hex(),
+            "fffffffffffffffffffffffffffffffffffffffffffffffffc87d25316270000"
+        );
+        assert_eq!(
+            encode_single(&ParamType::Ufixed(128, 18), "1.5"),
+            encode_single(&ParamType::Uint(256), "1500000000000000000")
+        );
+        assert_eq!(
+            encode_single(&ParamType::Ufixed(8, 1), "25.5"),
+            encode_single(&ParamType::Uint(8), "255")
+        );
+        match encode_single(&ParamType::Ufixed(8, 1), "25.6") {
+            Err(Error::Overflow(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(
+            encode_single(&ParamType::Fixed(8, 1), "-12.8"),
+            encode_single(&ParamType::Int(8), "-128")
+        );
+        assert!(encode_single(&ParamType::Fixed(8, 1), "12.8").is_err());
+        assert!(encode_single(&ParamType::Fixed(128, 2), "1.234").is_err());
+        assert!(encode_single(&ParamType::Ufixed(128, 2), "-1").is_err());
+        assert!(encode_single(&ParamType::Fixed(128, 2), "1e3").is_err());
+
+        let param_type = ParamType::from_str("fixed128x18[]").unwrap();
+        let encoded = encode_params(&[(param_type.clone(), "[1.5,-0.25,3]")]).unwrap();
+        assert_eq!(decode(&[param_type], &encoded), Ok(vec!["[1.5,-0.25,3]".to_owned()]));
+    }
+
+    #[test]
+    fn test_encode_single_ufixed() {
+        assert_eq!(
+            encode_single(&ParamType::Ufixed(8, 2), "2.55"),
+            encode_single(&ParamType::Uint(8), "255")
+        );
+        assert_eq!(
+            encode_single(&ParamType::Ufixed(8, 2), "2.56").map_err(|e| e.to_string()),
+            Err("Overflow value=2.56, type=Ufixed(8, 2)".to_owned())
+        );
+        assert_eq!(
+            encode_single(&ParamType::Ufixed(16, 3), " 0.5 "),
+            encode_single(&ParamType::Uint(16), "500")
+        );
+        assert_eq!(
+            encode_single(&ParamType::Ufixed(256, 80), "0.0001"),
+            encode_single(&ParamType::Uint(256), &format!("1{}", "0".repeat(76)))
+        );
+        match encode_single(&ParamType::Ufixed(256, 80), "0.1") {
+            Err(Error::Overflow(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(encode_single(&ParamType::Ufixed(128, 18), ".").is_err());
+        assert!(encode_single(&ParamType::Ufixed(128, 18), "0x10").is_err());
+    }
+
+    #[test]
+    fn test_encode_uint_bytes() {
+        assert_eq!(
+            encode_uint_bytes(&[0x00, 0x01, 0x00], 16),
+            encode_single(&ParamType::Uint(16), "256")
+        );
+        assert_eq!(
+            encode_uint_bytes(&[0xde, 0xad, 0xbe, 0xef], 256),
+            encode_single(&ParamType::Uint(256), "0xdeadbeef")
+        );
+        assert_eq!(encode_uint_bytes(&[], 8), encode_single(&ParamType::Uint(8), "0"));
+        assert!(encode_uint_bytes(&[0x01, 0x00], 8).is_err());
+        assert!(encode_uint_bytes(&[0x01], 7).is_err());
+    }
+
+    #[test]
+    fn test_encode_single_bool() {
         let expected_false = "0000000000000000000000000000000000000000000000000000000000000000"
             .from_hex()
             .unwrap();
@@ -384,4 +1535,812 @@ mod tests {
         assert_eq!(encode_single(&param_type, "true").unwrap(), expected_true);
         assert_eq!(encode_single(&param_type, "false").unwrap(), expected_false);
     }
+
+    #[test]
+    fn test_split_values() {
+        assert_eq!(split_values("[]", '[', ']'), Ok(vec![]));
+        assert_eq!(
+            split_values("[1, [2,3], (4,5)]", '[', ']'),
+            Ok(vec!["1", "[2,3]", "(4,5)"])
+        );
+        assert_eq!(
+            split_values(r#"("a,b",c)"#, '(', ')'),
+            Ok(vec!["a,b", "c"])
+        );
+        assert!(split_values("1,2", '[', ']').is_err());
+    }
+
+    #[test]
+    fn test_encode_single_address() {
+        let expected = "0000000000000000000000005b38da6a701c568545dcfcb03fcb875f56beddc4"
+            .from_hex()
+            .unwrap();
+        assert_eq!(
+            encode_single(&ParamType::Address, "0x5B38Da6a701c568545dCfcB03FcB875f56beddC4"),
+            Ok(expected)
+        );
+        assert_eq!(
+            encode_single(&ParamType::Address, "0x5b38"),
+            Err(Error::InvalidLength {
+                expected: 20,
+                actual: 2,
+            })
+        );
+        assert_eq!(
+            encode_single(&ParamType::Address, "vitalik.eth"),
+            Err(Error::InvalidValue(
+                "Invalid address value=vitalik.eth, ENS names are not resolved, use the 0x hex address"
+                    .to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_params_encode() {
+        let mut params = Params::new();
+        params.push(ParamType::Uint(256), "3");
+        params.push(ParamType::String, "abc");
+        let expected = "0000000000000000000000000000000000000000000000000000000000000003\
+                        0000000000000000000000000000000000000000000000000000000000000040\
+                        0000000000000000000000000000000000000000000000000000000000000003\
+                        6162630000000000000000000000000000000000000000000000000000000000"
+            .from_hex()
+            .unwrap();
+        assert_eq!(params.encode(), Ok(expected));
+    }
+
+    #[test]
+    fn test_params_encode_with_selector() {
+        let items = vec![
+            (ParamType::Address, "0x5b38da6a701c568545dcfcb03fcb875f56beddc4"),
+            (ParamType::Uint(256), "1000"),
+        ];
+        let mut params = Params::new();
+        for (param_type, value_str) in &items {
+            params.push(param_type.clone(), value_str);
+        }
+        let encoded = params.encode_with_selector("transfer").unwrap();
+        assert_eq!(encoded[..4].to_hex(), "a9059cbb");
+        assert_eq!(Ok(encoded), encode_function_call("transfer", &items));
+    }
+
+    #[test]
+    fn test_encode_function_call_no_params() {
+        let encoded = encode_function_call("pause", &[]).unwrap();
+        assert_eq!(encoded.to_hex(), "8456cb59");
+        assert_eq!(Params::new().encode_with_selector("pause"), Ok(encoded));
+    }
+
+    #[test]
+    fn test_encode_input() {
+        let to = "0x5b38da6a701c568545dcfcb03fcb875f56beddc4";
+        assert_eq!(
+            encode_input("transfer(address, uint)", &[to, "100"]).unwrap().to_hex(),
+            "a9059cbb\
+             0000000000000000000000005b38da6a701c568545dcfcb03fcb875f56beddc4\
+             0000000000000000000000000000000000000000000000000000000000000064"
+        );
+        assert_eq!(encode_input("pause()", &[]).unwrap().to_hex(), "8456cb59");
+        assert_eq!(
+            encode_input("transfer(address,uint256)", &[to]),
+            Err(Error::InvalidLength {
+                expected: 2,
+                actual: 1
+            })
+        );
+        assert!(encode_input("transfer", &[]).is_err());
+    }
+
+    #[test]
+    fn test_encode_constructor() {
+        let bytecode = [0x60, 0x80, 0x60, 0x40];
+        let types = [ParamType::String, ParamType::Uint(8)];
+        let encoded = encode_constructor(&bytecode, &types, &["Token", "18"]).unwrap();
+        assert_eq!(&encoded[..4], &bytecode);
+        assert_eq!(
+            encoded[4..].to_vec(),
+            encode_params(&[(ParamType::String, "Token"), (ParamType::Uint(8), "18")]).unwrap()
+        );
+        assert_eq!(encode_constructor(&bytecode, &[], &[]), Ok(bytecode.to_vec()));
+        assert_eq!(
+            encode_constructor(&bytecode, &types, &["Token"]),
+            Err(Error::InvalidLength {
+                expected: 2,
+                actual: 1
+            })
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing_spans() {
+        use std::sync::{Arc, Mutex};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        /// Records the names of created spans
+        struct SpanNames(Arc<Mutex<Vec<&'static str>>>);
+
+        impl Subscriber for SpanNames {
+            fn enabled(&self, _: &Metadata) -> bool {
+                true
+            }
+            fn new_span(&self, attrs: &Attributes) -> Id {
+                let mut names = self.0.lock().unwrap();
+                names.push(attrs.metadata().name());
+                Id::from_u64(names.len() as u64)
+            }
+            fn record(&self, _: &Id, _: &Record) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, _: &Event) {}
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let names = Arc::new(Mutex::new(Vec::new()));
+        tracing::subscriber::with_default(SpanNames(names.clone()), || {
+            let encoded = encode_params(&[(ParamType::Uint(256), "1")]).unwrap();
+            decode(&[ParamType::Uint(256)], &encoded).unwrap();
+        });
+        assert_eq!(*names.lock().unwrap(), vec!["encode_params", "decode"]);
+    }
+
+    #[test]
+    fn test_calldata_gas() {
+        assert_eq!(calldata_gas(&[]), 0);
+        assert_eq!(calldata_gas(&[0, 0, 1, 0xff]), 2 * 4 + 2 * 16);
+        // Selector and address are all non-zero bytes, 1000 takes two bytes
+        let encoded = encode_function_call(
+            "transfer",
+            &[
+                (ParamType::Address, "0x5b38da6a701c568545dcfcb03fcb875f56beddc4"),
+                (ParamType::Uint(256), "1000"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(calldata_gas(&encoded), (4 + 20 + 2) * 16 + (12 + 30) * 4);
+    }
+
+    #[test]
+    fn test_params_encode_repeated() {
+        let items = [
+            ("string", "abc"),
+            ("uint256", "1"),
+            ("string", "abc"),
+            ("bytes", "abc"),
+            ("uint256[]", "[1,2]"),
+            ("string", "abc"),
+            ("uint256[]", "[1,2]"),
+        ];
+        let types: Vec<ParamType> = items
+            .iter()
+            .map(|(type_str, _)| ParamType::from_str(type_str).unwrap())
+            .collect();
+        let mut params = Params::new();
+        for (param_type, (_, value_str)) in types.iter().zip(items.iter()) {
+            params.push(param_type.clone(), value_str);
+        }
+        let uncached = encode_sequence(
+            types
+                .iter()
+                .zip(items.iter().map(|(_, value_str)| *value_str)),
+        );
+        assert_eq!(params.encode(), uncached);
+    }
+
+    fn _assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_send_sync() {
+        _assert_send_sync::<ParamType>();
+        _assert_send_sync::<Token>();
+        _assert_send_sync::<Error>();
+        _assert_send_sync::<Params>();
+        _assert_send_sync::<Contract>();
+    }
+
+    #[test]
+    fn test_from_components() {
+        let components = vec![ParamType::Address, ParamType::String];
+        let tuple = ParamType::tuple_from_components(&components);
+        assert_eq!(tuple, ParamType::Tuple(components.clone()));
+        assert_eq!(ParamType::from_components("tuple", &components), Ok(tuple.clone()));
+        assert_eq!(
+            ParamType::from_components("tuple[]", &components),
+            Ok(ParamType::Array(Box::new(tuple.clone())))
+        );
+        assert_eq!(
+            ParamType::from_components("tuple[3][]", &components),
+            Ok(ParamType::Array(Box::new(ParamType::FixedArray(
+                Box::new(tuple),
+                3
+            ))))
+        );
+        assert_eq!(
+            ParamType::from_components("uint8", &components),
+            Ok(ParamType::Uint(8))
+        );
+        assert!(ParamType::from_components("tuples", &components).is_err());
+    }
+
+    #[test]
+    fn test_encode_type_component_count() {
+        let count = |s: &str| ParamType::from_str(s).unwrap().encode_type_component_count();
+        assert_eq!(count("uint256"), 1);
+        assert_eq!(count("string"), 1);
+        assert_eq!(count("()"), 0);
+        assert_eq!(count("bytes32[3]"), 3);
+        assert_eq!(count("(uint256,(bool,address))"), 3);
+        assert_eq!(count("(uint256,string)[2]"), 1);
+    }
+
+    #[test]
+    fn test_encode_nested_static_tuple_member() {
+        let param_type = ParamType::from_str("(uint8,(uint8,uint8),string)").unwrap();
+        let mut params = Params::new();
+        params.push(param_type, "(1,(2,3),ab)");
+        params.push(ParamType::String, "cd");
+        // Head of the outer list: offset of the tuple, offset of the string;
+        // head of the tuple: 1, 2, 3 inline, then the offset of its string
+        let expected = "0000000000000000000000000000000000000000000000000000000000000040\
+                        0000000000000000000000000000000000000000000000000000000000000100\
+                        0000000000000000000000000000000000000000000000000000000000000001\
+                        0000000000000000000000000000000000000000000000000000000000000002\
+                        0000000000000000000000000000000000000000000000000000000000000003\
+                        0000000000000000000000000000000000000000000000000000000000000080\
+                        0000000000000000000000000000000000000000000000000000000000000002\
+                        6162000000000000000000000000000000000000000000000000000000000000\
+                        0000000000000000000000000000000000000000000000000000000000000002\
+                        6364000000000000000000000000000000000000000000000000000000000000"
+            .from_hex()
+            .unwrap();
+        assert_eq!(params.encode(), Ok(expected));
+    }
+
+    #[test]
+    fn test_empty_tuple() {
+        let param_type = ParamType::from_str("()").unwrap();
+        assert_eq!(param_type, ParamType::Tuple(vec![]));
+        assert_eq!(param_type.to_string(), "()");
+        assert!(!param_type.is_dynamic());
+        assert_eq!(param_type.fixed_size(), Some(0));
+        assert_eq!(encode_value(&param_type, "()"), Ok(vec![]));
+
+        let types = [param_type.clone(), ParamType::String, param_type];
+        let values = ["()", "ab", "( )"];
+        let mut params = Params::new();
+        for (param_type, value_str) in types.iter().zip(values.iter()) {
+            params.push(param_type.clone(), value_str);
+        }
+        let expected = "0000000000000000000000000000000000000000000000000000000000000020\
+                        0000000000000000000000000000000000000000000000000000000000000002\
+                        6162000000000000000000000000000000000000000000000000000000000000"
+            .from_hex()
+            .unwrap();
+        assert_eq!(params.encoded_size(), Ok(expected.len()));
+        assert_eq!(params.encode(), Ok(expected.clone()));
+        assert_eq!(
+            ::decode(&types, &expected),
+            Ok(vec!["()".to_owned(), "ab".to_owned(), "()".to_owned()])
+        );
+    }
+
+    #[test]
+    fn test_fixed_size() {
+        assert_eq!(ParamType::from_str("uint8").unwrap().fixed_size(), Some(32));
+        assert_eq!(ParamType::from_str("bytes32[3]").unwrap().fixed_size(), Some(96));
+        assert_eq!(
+            ParamType::from_str("(uint256,(bool,address[2]))").unwrap().fixed_size(),
+            Some(128)
+        );
+        assert_eq!(ParamType::from_str("string").unwrap().fixed_size(), None);
+        assert_eq!(ParamType::from_str("uint256[]").unwrap().fixed_size(), None);
+        assert_eq!(ParamType::from_str("(uint256,bytes)").unwrap().fixed_size(), None);
+
+        // Zero length arrays are static even with a dynamic element type
+        let param_type = ParamType::from_str("string[0]").unwrap();
+        assert_eq!(param_type.fixed_size(), Some(0));
+        assert!(!param_type.is_dynamic());
+        let params = [(param_type, "[]"), (ParamType::Uint(8), "1")];
+        assert_eq!(encode_params(&params), encode_params(&params[1..]));
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(
+            ParamType::from_str("(uint256,bool)").unwrap().describe(),
+            "(uint256,bool) (static, 64 bytes)"
+        );
+        assert_eq!(ParamType::from_str("uint256[]").unwrap().describe(), "uint256[] (dynamic)");
+        assert_eq!(
+            ParamType::from_str("(address,string)[2]").unwrap().describe(),
+            "(address,string)[2] (dynamic)"
+        );
+    }
+
+    #[test]
+    fn test_params_push_raw() {
+        let blob = encode_params(&[
+            (ParamType::Uint(256), "5"),
+            (ParamType::Address, "0x5b38da6a701c568545dcfcb03fcb875f56beddc4"),
+        ]).unwrap();
+        let mut params = Params::new();
+        params.push(ParamType::String, "abc");
+        params.push_raw(blob);
+        params.push(ParamType::Bool, "true");
+        let expected = encode_params(&[
+            (ParamType::String, "abc"),
+            (ParamType::Uint(256), "5"),
+            (ParamType::Address, "0x5b38da6a701c568545dcfcb03fcb875f56beddc4"),
+            (ParamType::Bool, "true"),
+        ]).unwrap();
+        assert_eq!(params.encoded_size(), Ok(expected.len()));
+        assert_eq!(params.encode(), Ok(expected));
+    }
+
+    #[test]
+    fn test_encode_params_to() {
+        let long_bytes = "ab".repeat(40);
+        let params = [
+            (ParamType::Uint(256), "3"),
+            (ParamType::String, "abc"),
+            (ParamType::from_str("(bool,bytes)[]").unwrap(), "[(true,0x01),(false,0x)]"),
+            (ParamType::from_str("uint8[2]").unwrap(), "[1,2]"),
+            (ParamType::Bytes, long_bytes.as_str()),
+        ];
+        let mut buf = Vec::new();
+        encode_params_to(&mut buf, &params).unwrap();
+        assert_eq!(Ok(buf), encode_params(&params));
+
+        let mut short_buf = [0u8; 40];
+        match encode_params_to(&mut &mut short_buf[..], &params) {
+            Err(Error::Io(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_params_encode_dynamic_tail() {
+        let mut params = Params::new();
+        params.push(ParamType::Bool, "true");
+        params.push(ParamType::String, "abc");
+        assert_eq!(
+            params.encode().unwrap().to_hex(),
+            "0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000000000000000000000000000000000000000000040\
+             0000000000000000000000000000000000000000000000000000000000000003\
+             6162630000000000000000000000000000000000000000000000000000000000"
+        );
+    }
+
+    #[test]
+    fn test_params_validate() {
+        let mut params = Params::new();
+        params.push(ParamType::Uint(256), "1");
+        params.push(ParamType::from_str("uint256[]").unwrap(), "[1,2]");
+        assert_eq!(params.validate(), Ok(()));
+        params.push(ParamType::from_str("uint256[]").unwrap(), "3");
+        params.push(ParamType::Bool, "maybe");
+        let err = params.validate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("in param 2 of type uint256[]: {}", err.root())
+        );
+        assert_eq!(params.encode(), Err(err));
+    }
+
+    #[test]
+    fn test_params_encoded_size() {
+        let long_bytes = "ab".repeat(40);
+        let lists: Vec<Vec<(&str, &str)>> = vec![
+            vec![],
+            vec![("uint256", "3"), ("bool", "true")],
+            vec![("string", "abc"), ("bytes", &long_bytes)],
+            vec![("uint256[][]", "[[1,2],[3]]"), ("string[]", "[one,two,three]")],
+            vec![("(uint256,string)[2]", "[(1,a),(2,bc)]"), ("bytes3[2]", "[abc,def]")],
+        ];
+        for list in lists {
+            let mut params = Params::new();
+            for (type_str, value_str) in &list {
+                params.push(ParamType::from_str(type_str).unwrap(), value_str);
+            }
+            assert_eq!(
+                params.encoded_size(),
+                Ok(params.encode().unwrap().len()),
+                "{:?}",
+                list
+            );
+        }
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(
+            encode(&ParamType::Uint(256), "3"),
+            encode_single(&ParamType::Uint(256), "3")
+        );
+        let mut expected = encode_usize(32);
+        expected.extend(encode_single(&ParamType::String, "abc").unwrap());
+        assert_eq!(encode(&ParamType::String, "abc"), Ok(expected));
+        assert_eq!(
+            encode(&ParamType::from_str("uint8[]").unwrap(), "[1,2]"),
+            encode_params(&[(ParamType::from_str("uint8[]").unwrap(), "[1,2]")])
+        );
+    }
+
+    #[test]
+    fn test_encode_bytes_exact() {
+        assert_eq!(
+            encode_bytes_exact("0xdeadbeef", 4),
+            encode_single(&ParamType::Bytes, "0xdeadbeef")
+        );
+        assert_eq!(
+            encode_bytes_exact("0xdeadbeef", 32),
+            Err(Error::InvalidLength {
+                expected: 32,
+                actual: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn test_encode_params_with() {
+        // Reads bools as yes/no, everything else with the default rules
+        struct YesNoTokenizer;
+
+        impl Tokenizer for YesNoTokenizer {
+            fn tokenize(&self, param_type: &ParamType, value: &str) -> Result<Token, Error> {
+                match (param_type, value) {
+                    (ParamType::Bool, "yes") => Ok(Token::Bool(true)),
+                    (ParamType::Bool, "no") => Ok(Token::Bool(false)),
+                    (ParamType::Bool, _) => Err(Error::InvalidValue(format!(
+                        "Invalid bool value: {}",
+                        value
+                    ))),
+                    _ => DefaultTokenizer::tokenize_nested(self, param_type, value),
+                }
+            }
+        }
+
+        let types = [
+            ParamType::Bool,
+            ParamType::from_str("(uint8,bool)[]").unwrap(),
+        ];
+        let params: Vec<(ParamType, &str)> = types
+            .iter()
+            .cloned()
+            .zip(["no", "[(1,yes),(2,no)]"].iter().cloned())
+            .collect();
+        let expected: Vec<(ParamType, &str)> = types
+            .iter()
+            .cloned()
+            .zip(["false", "[(1,true),(2,false)]"].iter().cloned())
+            .collect();
+        assert_eq!(
+            encode_params_with(&params, &YesNoTokenizer),
+            encode_params(&expected)
+        );
+        assert_eq!(
+            encode_params_with(&expected, &DefaultTokenizer),
+            encode_params(&expected)
+        );
+        assert!(encode_params_with(&expected, &YesNoTokenizer).is_err());
+    }
+
+    #[test]
+    fn test_encode_params_with_wrong_tokens() {
+        // Ignores the declared type and always returns the same token
+        struct ConstTokenizer(Token);
+
+        impl Tokenizer for ConstTokenizer {
+            fn tokenize(&self, _: &ParamType, _: &str) -> Result<Token, Error> {
+                Ok(self.0.clone())
+            }
+        }
+
+        let cases = [
+            ("bool", Token::Uint(U256::one())),
+            ("bytes32", Token::FixedBytes(vec![0; 33])),
+            ("uint8[2]", Token::FixedArray(vec![Token::Uint(U256::one())])),
+        ];
+        for (type_str, token) in cases.iter() {
+            let params = [(ParamType::from_str(type_str).unwrap(), "1")];
+            let tokenizer = ConstTokenizer(token.clone());
+            assert!(encode_params_with(&params, &tokenizer).is_err(), "{}", type_str);
+        }
+        let params = [(ParamType::Bool, "1")];
+        assert_eq!(
+            encode_params_with(&params, &ConstTokenizer(Token::Bool(true))),
+            encode_params(&[(ParamType::Bool, "true")])
+        );
+    }
+
+    #[test]
+    fn test_encode_single_fixed_bytes() {
+        let param_type = ParamType::from_str("bytes1").unwrap();
+        let encoded = encode_single(&param_type, "0xab").unwrap();
+        assert_eq!(encoded.len(), 32);
+        assert_eq!(
+            encoded,
+            "ab00000000000000000000000000000000000000000000000000000000000000"
+                .from_hex()
+                .unwrap()
+        );
+
+        let param_type = ParamType::from_str("bytes31").unwrap();
+        let value_str = format!("0x{}", "ff".repeat(31));
+        let encoded = encode_single(&param_type, &value_str).unwrap();
+        assert_eq!(encoded.len(), 32);
+        assert_eq!(&encoded[..31], &[0xffu8; 31][..]);
+        assert_eq!(encoded[31], 0);
+
+        assert!(encode_single(&param_type, &format!("0x{}", "ff".repeat(32))).is_err());
+        assert!(encode_single(&ParamType::FixedBytes(40), "0xab").is_err());
+    }
+
+    #[test]
+    fn test_pad_to_word() {
+        assert_eq!(pad_to_word(0), 0);
+        assert_eq!(pad_to_word(1), 32);
+        assert_eq!(pad_to_word(32), 32);
+        assert_eq!(pad_to_word(33), 64);
+    }
+
+    #[test]
+    fn test_encode_usize() {
+        assert_eq!(encode_usize(0), vec![0u8; 32]);
+        let encoded = encode_usize(0x1234);
+        assert_eq!(&encoded[30..], &[0x12, 0x34]);
+        assert_eq!(U256::from(encoded.as_slice()), U256::from(0x1234));
+
+        // A length beyond 2^32, as if a huge `bytes` was encoded
+        if std::mem::size_of::<usize>() == 8 {
+            let len = 0x0102_0304_0506_0708u64 as usize;
+            let encoded = encode_usize(len);
+            assert_eq!(&encoded[..24], &[0u8; 24][..]);
+            assert_eq!(&encoded[24..], &[1, 2, 3, 4, 5, 6, 7, 8]);
+        }
+        let encoded = encode_usize(usize::MAX);
+        assert_eq!(U256::from(encoded.as_slice()), U256::from(usize::MAX as u64));
+    }
+
+    #[test]
+    fn test_encode_hash() {
+        // keccak256(abi.encode(uint256(1000), address(0x5b38...))), from ethabi 18
+        let params = [
+            (ParamType::Uint(256), "1000"),
+            (ParamType::Address, "0x5b38da6a701c568545dcfcb03fcb875f56beddc4"),
+        ];
+        assert_eq!(
+            encode_hash(&params).unwrap().to_hex(),
+            "ae84efedbad456cc90a19d19899e83179f981e44086479c1be5bb6badf9b39ab"
+        );
+    }
+
+    #[test]
+    fn test_encode_packed() {
+        // The example from the Solidity docs
+        let params = [
+            (ParamType::Int(16), "-1"),
+            (ParamType::FixedBytes(1), "0x42"),
+            (ParamType::Uint(16), "0x03"),
+            (ParamType::String, "Hello, world!"),
+        ];
+        assert_eq!(
+            encode_packed(&params).unwrap().to_hex(),
+            "ffff42000348656c6c6f2c20776f726c6421"
+        );
+
+        let params = [
+            (ParamType::Address, "0x5b38da6a701c568545dcfcb03fcb875f56beddc4"),
+            (ParamType::Bool, "true"),
+            (ParamType::Bytes, "0xdead"),
+            (ParamType::Array(Box::new(ParamType::Uint(8))), "[1,2]"),
+        ];
+        assert_eq!(
+            encode_packed(&params).unwrap().to_hex(),
+            "5b38da6a701c568545dcfcb03fcb875f56beddc401dead\
+             0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000000000000000000000000000000000000000000002"
+        );
+        assert_eq!(
+            decode_packed(
+                &[ParamType::Address, ParamType::Bool, ParamType::Bytes],
+                &encode_packed(&params[..3]).unwrap()
+            ),
+            Ok(vec![
+                params[0].1.to_owned(),
+                params[1].1.to_owned(),
+                params[2].1.to_owned()
+            ])
+        );
+
+        for param_type in &["(uint8,bool)", "string[]", "uint8[][]"] {
+            let param_type = ParamType::from_str(param_type).unwrap();
+            match encode_packed(&[(param_type, "[]")]) {
+                Err(Error::Unsupported(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+        let bools = ParamType::FixedArray(Box::new(ParamType::Bool), 2);
+        assert!(encode_packed(&[(bools, "[true]")]).is_err());
+    }
+
+    #[test]
+    fn test_encode_params_pad_dynamic() {
+        let params = [(ParamType::Bytes, "0xdeadbeef"), (ParamType::Uint(8), "1")];
+        let padded = "0000000000000000000000000000000000000000000000000000000000000040\
+             0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000000000000000000000000000000000000000000004\
+             deadbeef00000000000000000000000000000000000000000000000000000000";
+        let encoded = encode_params_with_options(&params, &EncodeOptions::default()).unwrap();
+        assert_eq!(encoded.to_hex(), padded);
+        assert_eq!(Ok(encoded), encode_params(&params));
+
+        let options = EncodeOptions { pad_dynamic: false };
+        let encoded = encode_params_with_options(&params, &options).unwrap();
+        assert_eq!(encoded.len(), 3 * 32 + 4);
+        assert_eq!(encoded.to_hex(), padded.trim_end_matches('0'));
+    }
+
+    #[test]
+    fn test_encode_bytes32_array() {
+        let param_type = ParamType::Array(Box::new(ParamType::FixedBytes(32)));
+        let proof = [
+            "1111111111111111111111111111111111111111111111111111111111111111",
+            "2222222222222222222222222222222222222222222222222222222222222222",
+            "3333333333333333333333333333333333333333333333333333333333333333",
+        ];
+        let value_str = format!("[0x{},0x{},0x{}]", proof[0], proof[1], proof[2]);
+        let encoded = encode_params(&[(param_type.clone(), &value_str)]).unwrap();
+        let expected = format!(
+            "0000000000000000000000000000000000000000000000000000000000000020\
+             0000000000000000000000000000000000000000000000000000000000000003\
+             {}{}{}",
+            proof[0], proof[1], proof[2]
+        );
+        assert_eq!(encoded.to_hex(), expected);
+        assert_eq!(decode(&[param_type], &encoded), Ok(vec![value_str]));
+    }
+
+    #[test]
+    fn test_encode_tuple() {
+        let address = "0x5b38da6a701c568545dcfcb03fcb875f56beddc4";
+        let cases = [
+            ("(uint256,bool)", "(1,true)".to_owned()),
+            ("(string,uint256[])", "(abc,[1,2])".to_owned()),
+            ("(address,(bool,bytes))", format!("({},(false,0xdead))", address)),
+            ("(uint256,string)[]", "[(1,a),(2,bc)]".to_owned()),
+            ("((uint8,string)[2],bool)", "([(1,x),(2,y)],true)".to_owned()),
+        ];
+        for (type_str, value_str) in &cases {
+            let param_type = ParamType::from_str(type_str).unwrap();
+            let encoded = encode_params(&[(param_type.clone(), value_str)]).unwrap();
+            let token = tokenize(&param_type, value_str).unwrap();
+            assert_eq!(
+                encode_tokens(std::slice::from_ref(&param_type), &[token]),
+                Ok(encoded.clone()),
+                "{}",
+                type_str
+            );
+            assert_eq!(decode(&[param_type], &encoded), Ok(vec![value_str.clone()]));
+        }
+    }
+
+    #[test]
+    fn test_encode_fixed_array() {
+        // Static elements are inline in the head, without an offset
+        let param_type = ParamType::from_str("uint8[2]").unwrap();
+        assert!(!param_type.is_dynamic());
+        assert_eq!(
+            encode_params(&[(param_type.clone(), "[1,2]"), (ParamType::Bool, "true")])
+                .unwrap()
+                .to_hex(),
+            "0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000000000000000000000000000000000000000000002\
+             0000000000000000000000000000000000000000000000000000000000000001"
+        );
+        assert_eq!(
+            encode_params(&[(param_type.clone(), "[1,2,3]")]).map_err(|e| e.root().clone()),
+            Err(Error::InvalidLength {
+                expected: 2,
+                actual: 3
+            })
+        );
+
+        // Dynamic elements make an offset table behind the array's own offset
+        let param_type = ParamType::from_str("uint256[][2]").unwrap();
+        assert_eq!(
+            encode_params(&[(param_type, "[[7],[]]")]).unwrap().to_hex(),
+            "0000000000000000000000000000000000000000000000000000000000000020\
+             0000000000000000000000000000000000000000000000000000000000000040\
+             0000000000000000000000000000000000000000000000000000000000000080\
+             0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000000000000000000000000000000000000000000007\
+             0000000000000000000000000000000000000000000000000000000000000000"
+        );
+    }
+
+    #[test]
+    fn test_encode_string_fixed_array() {
+        let param_type = ParamType::from_str("string[2]").unwrap();
+        assert_eq!(param_type, ParamType::FixedArray(Box::new(ParamType::String), 2));
+        assert!(param_type.is_dynamic());
+        let encoded = encode_params(&[(param_type.clone(), "[abc,hello world!]")]).unwrap();
+        // Offset table relative to the array start, no length word
+        assert_eq!(
+            encoded.to_hex(),
+            "0000000000000000000000000000000000000000000000000000000000000020\
+             0000000000000000000000000000000000000000000000000000000000000040\
+             0000000000000000000000000000000000000000000000000000000000000080\
+             0000000000000000000000000000000000000000000000000000000000000003\
+             6162630000000000000000000000000000000000000000000000000000000000\
+             000000000000000000000000000000000000000000000000000000000000000c\
+             68656c6c6f20776f726c64210000000000000000000000000000000000000000"
+        );
+        assert_eq!(
+            decode(&[param_type], &encoded),
+            Ok(vec!["[abc,hello world!]".to_owned()])
+        );
+
+        let param_type = ParamType::from_str("bytes[2]").unwrap();
+        let value_str = "[0x616263,0x68656c6c6f20776f726c6421]";
+        assert_eq!(encode_params(&[(param_type.clone(), value_str)]), Ok(encoded.clone()));
+        assert_eq!(decode(&[param_type], &encoded), Ok(vec![value_str.to_owned()]));
+    }
+
+    #[test]
+    fn test_encode_single_array() {
+        let param_type = ParamType::from_str("uint256[]").unwrap();
+        assert_eq!(
+            encode_single(&param_type, "[1,2]").unwrap().to_hex(),
+            "0000000000000000000000000000000000000000000000000000000000000002\
+             0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000000000000000000000000000000000000000000002"
+        );
+        let address = "0x5b38da6a701c568545dcfcb03fcb875f56beddc4";
+        assert_eq!(
+            encode_single(&ParamType::from_str("address[]").unwrap(), &format!("[{}]", address))
+                .unwrap()
+                .to_hex(),
+            "0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000005b38da6a701c568545dcfcb03fcb875f56beddc4"
+        );
+        // Element offsets are relative to the start of the elements
+        assert_eq!(
+            encode_single(&ParamType::from_str("string[]").unwrap(), "[a,bc]")
+                .unwrap()
+                .to_hex(),
+            "0000000000000000000000000000000000000000000000000000000000000002\
+             0000000000000000000000000000000000000000000000000000000000000040\
+             0000000000000000000000000000000000000000000000000000000000000080\
+             0000000000000000000000000000000000000000000000000000000000000001\
+             6100000000000000000000000000000000000000000000000000000000000000\
+             0000000000000000000000000000000000000000000000000000000000000002\
+             6263000000000000000000000000000000000000000000000000000000000000"
+        );
+        assert_eq!(
+            Ok(encode_single(&param_type, "[1,2]").unwrap()),
+            encode(&param_type, "[1,2]").map(|encoded| encoded[32..].to_vec())
+        );
+    }
+
+    #[test]
+    fn test_encode_bool_array() {
+        let param_type = ParamType::Array(Box::new(ParamType::Bool));
+        let encoded = encode_params(&[(param_type.clone(), "[true, false,true]")]).unwrap();
+        assert_eq!(
+            encoded.to_hex(),
+            "0000000000000000000000000000000000000000000000000000000000000020\
+             0000000000000000000000000000000000000000000000000000000000000003\
+             0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000000000000000000000000000000000000000000000\
+             0000000000000000000000000000000000000000000000000000000000000001"
+        );
+        assert!(encode_params(&[(param_type.clone(), "[true,1]")]).is_err());
+        assert_eq!(
+            decode(&[param_type], &encoded),
+            Ok(vec!["[true,false,true]".to_owned()])
+        );
+    }
 }
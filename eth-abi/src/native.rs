@@ -0,0 +1,101 @@
+//! Decoded values as `ethereum_types` types
+
+use ethereum_types::{Address, H256, U256};
+
+use super::{decode_tokens, Bytes, Error, ParamType, Token};
+
+/// Decoded value, like `Token` but with `bytes32` as `H256`
+#[derive(Debug, Clone, PartialEq)]
+pub enum NativeValue {
+    /// Address
+    Address(Address),
+    /// 32-byte word, from `bytes32`
+    H256(H256),
+    /// Fixed size bytes shorter than 32
+    FixedBytes(Bytes),
+    /// Dynamic bytes
+    Bytes(Bytes),
+    /// Signed integer in two's complement
+    Int(U256),
+    /// Unsigned integer
+    Uint(U256),
+    /// Boolean
+    Bool(bool),
+    /// String
+    String(String),
+    /// Fixed size or dynamic array
+    Array(Vec<NativeValue>),
+    /// Tuple
+    Tuple(Vec<NativeValue>),
+}
+
+impl From<Token> for NativeValue {
+    fn from(token: Token) -> Self {
+        let convert = |tokens: Vec<Token>| tokens.into_iter().map(NativeValue::from).collect();
+        match token {
+            Token::Address(address) => NativeValue::Address(address),
+            Token::FixedBytes(value_bytes) => {
+                if value_bytes.len() == 32 {
+                    let mut word = [0u8; 32];
+                    word.copy_from_slice(&value_bytes);
+                    NativeValue::H256(H256::from(word))
+                } else {
+                    NativeValue::FixedBytes(value_bytes)
+                }
+            }
+            Token::Bytes(value_bytes) => NativeValue::Bytes(value_bytes),
+            Token::Int(value) => NativeValue::Int(value),
+            Token::Uint(value) => NativeValue::Uint(value),
+            Token::Bool(value) => NativeValue::Bool(value),
+            Token::String(value) => NativeValue::String(value),
+            Token::FixedArray(tokens) | Token::Array(tokens) => NativeValue::Array(convert(tokens)),
+            Token::Tuple(tokens) => NativeValue::Tuple(convert(tokens)),
+        }
+    }
+}
+
+/// Decode params into `ethereum_types` values, unconsumed trailing bytes are
+/// ignored
+pub fn decode_native(types: &[ParamType], data: &[u8]) -> Result<Vec<NativeValue>, Error> {
+    let tokens = decode_tokens(types, data)?;
+    Ok(tokens.into_iter().map(NativeValue::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use hex::FromHex;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_decode_native() {
+        let types = [ParamType::Address, ParamType::Uint(256)];
+        let data = ::encode_params(&[
+            (ParamType::Address, "0x5b38da6a701c568545dcfcb03fcb875f56beddc4"),
+            (ParamType::Uint(256), "1000"),
+        ]).unwrap();
+        let address_bytes: Vec<u8> = "5b38da6a701c568545dcfcb03fcb875f56beddc4".from_hex().unwrap();
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&address_bytes);
+        assert_eq!(
+            decode_native(&types, &data),
+            Ok(vec![
+                NativeValue::Address(Address::from(address)),
+                NativeValue::Uint(U256::from(1000)),
+            ])
+        );
+
+        let types = [ParamType::from_str("(bytes32,bytes4)[1]").unwrap()];
+        let data = ::encode_params(&[(types[0].clone(), "[(0x01,0x02)]")]).unwrap();
+        let mut word = [0u8; 32];
+        word[0] = 1;
+        assert_eq!(
+            decode_native(&types, &data),
+            Ok(vec![NativeValue::Array(vec![NativeValue::Tuple(vec![
+                NativeValue::H256(H256::from(word)),
+                NativeValue::FixedBytes(vec![2, 0, 0, 0]),
+            ])])])
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Function and event signatures
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use tiny_keccak::keccak256;
+
+use super::{Error, Function, Param, ParamType};
+
+/// Canonical signature, e.g. `transfer(address,uint256)`
+pub fn signature(name: &str, types: &[ParamType]) -> String {
+    signature_of(name, types)
+}
+
+/// `signature` over borrowed types, e.g. straight from a function's inputs
+pub(crate) fn signature_of<'a, I>(name: &str, types: I) -> String
+where
+    I: IntoIterator<Item = &'a ParamType>,
+{
+    let type_names: Vec<String> = types.into_iter().map(|t| t.to_string()).collect();
+    format!("{}({})", name, type_names.join(","))
+}
+
+/// `selector` over borrowed types
+pub(crate) fn selector_of<'a, I>(name: &str, types: I) -> [u8; 4]
+where
+    I: IntoIterator<Item = &'a ParamType>,
+{
+    let hash = keccak256(signature_of(name, types).as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Parse a signature like `transfer(address,uint256)` into a function with
+/// unnamed inputs and no outputs
+pub fn parse_function(sig: &str) -> Result<Function, Error> {
+    let sig = sig.trim();
+    let invalid = || Error::InvalidType {
+        message: format!("Invalid function signature: {}", sig),
+        position: 0,
+    };
+    let open = sig.find('(').ok_or_else(invalid)?;
+    let name = sig[..open].trim();
+    if name.is_empty() || !sig.ends_with(')') {
+        return Err(invalid());
+    }
+    let inputs = match ParamType::from_str(&sig[open..]).map_err(|e| match e {
+        Error::InvalidType { message, position } => Error::InvalidType {
+            message,
+            position: open + position,
+        },
+        e => e,
+    })? {
+        ParamType::Tuple(types) => types
+            .into_iter()
+            .map(|kind| Param {
+                name: String::new(),
+                kind: Arc::new(kind),
+            })
+            .collect(),
+        _ => return Err(invalid()),
+    };
+    Ok(Function {
+        name: name.to_owned(),
+        inputs,
+        outputs: Vec::new(),
+    })
+}
+
+/// Function selector: the first 4 bytes of the signature's Keccak-256 hash
+pub fn selector(name: &str, types: &[ParamType]) -> [u8; 4] {
+    selector_of(name, types)
+}
+
+/// Selector of an already canonical signature like
+/// `transfer(address,uint256)`, hashed as is without parsing its types
+///
+/// Only the overall `name(...)` shape is checked and whitespace is rejected,
+/// use `parse_function` to canonicalize other spellings first.
+pub fn selector_from_sig(sig: &str) -> Result<[u8; 4], Error> {
+    let open = sig.find('(').unwrap_or(0);
+    if open == 0 || !sig.ends_with(')') || sig.contains(char::is_whitespace) {
+        return Err(Error::InvalidType {
+            message: format!("Invalid canonical signature: {}", sig),
+            position: 0,
+        });
+    }
+    let hash = keccak256(sig.as_bytes());
+    Ok([hash[0], hash[1], hash[2], hash[3]])
+}
+
+/// Event topic0: the Keccak-256 hash of the event signature
+pub fn event_topic(name: &str, types: &[ParamType]) -> [u8; 32] {
+    keccak256(signature_of(name, types).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use hex::ToHex;
+
+    #[test]
+    fn test_selector() {
+        let types = vec![ParamType::Address, ParamType::Uint(256)];
+        assert_eq!(signature("transfer", &types), "transfer(address,uint256)");
+        assert_eq!(selector("transfer", &types).to_hex(), "a9059cbb");
+    }
+
+    #[test]
+    fn test_selector_from_sig() {
+        assert_eq!(
+            selector_from_sig("transfer(address,uint256)").unwrap().to_hex(),
+            "a9059cbb"
+        );
+        assert_eq!(selector_from_sig("pause()"), Ok(selector("pause", &[])));
+        assert!(selector_from_sig("transfer").is_err());
+        assert!(selector_from_sig("(uint256)").is_err());
+        assert!(selector_from_sig("transfer(address, uint256)").is_err());
+    }
+
+    #[test]
+    fn test_event_topic() {
+        let types = vec![ParamType::Address, ParamType::Address, ParamType::Uint(256)];
+        assert_eq!(
+            event_topic("Transfer", &types).to_hex(),
+            "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+        );
+    }
+
+    #[test]
+    fn test_parse_function() {
+        let function = parse_function("transfer(address,uint256)").unwrap();
+        assert_eq!(function.name, "transfer");
+        assert_eq!(
+            function.input_types(),
+            vec![ParamType::Address, ParamType::Uint(256)]
+        );
+        assert_eq!(parse_function("pause()").unwrap().inputs, vec![]);
+        assert_eq!(
+            parse_function("f((uint256,bool)[],bytes)").unwrap().signature(),
+            "f((uint256,bool)[],bytes)"
+        );
+        assert_eq!(
+            parse_function("withdraw(address payable,uint256)").unwrap().signature(),
+            "withdraw(address,uint256)"
+        );
+        assert!(parse_function("transfer").is_err());
+        assert!(parse_function("(uint256)").is_err());
+    }
+}
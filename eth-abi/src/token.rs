@@ -0,0 +1,498 @@
+//! Typed values and their encoding
+
+use ethereum_types::{Address, U256};
+use hex::ToHex;
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+use std::fmt;
+
+use super::{encode_usize, layout_sequence, pad_to_word, Bytes, Error, ParamType, WORD_SIZE};
+
+/// Options controlling how tokens are encoded
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EncodeOptions {
+    /// Zero pad `bytes` and `string` content to a multiple of 32 bytes, as
+    /// the ABI requires. Turning it off is only meant for interop with
+    /// non-standard tools expecting unpadded dynamic content.
+    pub pad_dynamic: bool,
+}
+
+impl Default for EncodeOptions {
+    fn default() -> Self {
+        EncodeOptions { pad_dynamic: true }
+    }
+}
+
+/// Typed ABI value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    /// Address
+    Address(Address),
+    /// Fixed size bytes, at most 32
+    FixedBytes(Bytes),
+    /// Dynamic bytes
+    Bytes(Bytes),
+    /// Signed integer in two's complement
+    Int(U256),
+    /// Unsigned integer
+    Uint(U256),
+    /// Boolean
+    Bool(bool),
+    /// String
+    String(String),
+    /// Fixed size array
+    FixedArray(Vec<Token>),
+    /// Dynamic array
+    Array(Vec<Token>),
+    /// Tuple
+    Tuple(Vec<Token>),
+}
+
+/// Decoded ABI value borrowing its byte and string content from the
+/// decoded data, see `decode_token_refs`
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenRef<'a> {
+    /// Address
+    Address(Address),
+    /// Fixed size bytes, at most 32
+    FixedBytes(&'a [u8]),
+    /// Dynamic bytes
+    Bytes(&'a [u8]),
+    /// Signed integer in two's complement
+    Int(U256),
+    /// Unsigned integer
+    Uint(U256),
+    /// Boolean
+    Bool(bool),
+    /// String
+    String(&'a str),
+    /// Fixed size array
+    FixedArray(Vec<TokenRef<'a>>),
+    /// Dynamic array
+    Array(Vec<TokenRef<'a>>),
+    /// Tuple
+    Tuple(Vec<TokenRef<'a>>),
+}
+
+impl<'a> From<TokenRef<'a>> for Token {
+    fn from(token: TokenRef<'a>) -> Self {
+        let into_tokens = |tokens: Vec<TokenRef>| tokens.into_iter().map(Token::from).collect();
+        match token {
+            TokenRef::Address(address) => Token::Address(address),
+            TokenRef::FixedBytes(value_bytes) => Token::FixedBytes(value_bytes.to_vec()),
+            TokenRef::Bytes(value_bytes) => Token::Bytes(value_bytes.to_vec()),
+            TokenRef::Int(value) => Token::Int(value),
+            TokenRef::Uint(value) => Token::Uint(value),
+            TokenRef::Bool(value) => Token::Bool(value),
+            TokenRef::String(value) => Token::String(value.to_owned()),
+            TokenRef::FixedArray(tokens) => Token::FixedArray(into_tokens(tokens)),
+            TokenRef::Array(tokens) => Token::Array(into_tokens(tokens)),
+            TokenRef::Tuple(tokens) => Token::Tuple(into_tokens(tokens)),
+        }
+    }
+}
+
+impl Token {
+    fn kind_name(&self) -> &'static str {
+        match self {
+            Token::Address(_) => "address",
+            Token::FixedBytes(_) => "fixed bytes",
+            Token::Bytes(_) => "bytes",
+            Token::Int(_) => "int",
+            Token::Uint(_) => "uint",
+            Token::Bool(_) => "bool",
+            Token::String(_) => "string",
+            Token::FixedArray(_) => "fixed array",
+            Token::Array(_) => "array",
+            Token::Tuple(_) => "tuple",
+        }
+    }
+
+    /// Check if the token is encoded in the tail behind an offset
+    pub fn is_dynamic(&self) -> bool {
+        match self {
+            Token::Bytes(_) | Token::String(_) | Token::Array(_) => true,
+            Token::FixedArray(tokens) | Token::Tuple(tokens) => {
+                tokens.iter().any(|t| t.is_dynamic())
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Value syntax accepted by `tokenize`, `Int`s are shown with a leading `-`
+/// when negative rather than as their two's complement word
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let write_list = |f: &mut fmt::Formatter, tokens: &[Token], open, close| {
+            let values: Vec<String> = tokens.iter().map(|t| t.to_string()).collect();
+            write!(f, "{}{}{}", open, values.join(","), close)
+        };
+        match self {
+            Token::Address(address) => write!(f, "0x{}", address.to_hex()),
+            Token::FixedBytes(value_bytes) | Token::Bytes(value_bytes) => {
+                write!(f, "0x{}", value_bytes.to_hex())
+            }
+            Token::Int(value) => {
+                let (negative, value) = split_signed(*value);
+                write!(f, "{}{}", if negative { "-" } else { "" }, value)
+            }
+            Token::Uint(value) => write!(f, "{}", value),
+            Token::Bool(value) => write!(f, "{}", value),
+            Token::String(value) => write!(f, "{}", value),
+            Token::FixedArray(tokens) | Token::Array(tokens) => write_list(f, tokens, '[', ']'),
+            Token::Tuple(tokens) => write_list(f, tokens, '(', ')'),
+        }
+    }
+}
+
+/// Arrays and tuples as JSON arrays, bools as JSON bools, other values as
+/// their `Display` strings since integers may exceed JSON numbers
+impl Serialize for Token {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Token::Bool(value) => serializer.serialize_bool(*value),
+            Token::FixedArray(tokens) | Token::Array(tokens) | Token::Tuple(tokens) => {
+                let mut seq = serializer.serialize_seq(Some(tokens.len()))?;
+                for token in tokens {
+                    seq.serialize_element(token)?;
+                }
+                seq.end()
+            }
+            _ => serializer.collect_str(self),
+        }
+    }
+}
+
+/// Split a two's complement word into its sign and magnitude
+pub(crate) fn split_signed(value: U256) -> (bool, U256) {
+    if value.bit(255) {
+        (true, (!value) + U256::one())
+    } else {
+        (false, value)
+    }
+}
+
+/// Check that a signed two's complement word fits in `bits` bits
+pub(crate) fn fits_signed(value: U256, bits: usize) -> bool {
+    let magnitude = if value.bit(255) { !value } else { value };
+    magnitude.bits() < bits
+}
+
+fn check_count(param_type: &ParamType, expected: usize, actual: usize) -> Result<(), Error> {
+    if expected != actual {
+        return Err(Error::InvalidValue(format!(
+            "Invalid element count for type={}: expected={}, actual={}",
+            param_type, expected, actual
+        )));
+    }
+    Ok(())
+}
+
+/// Check that a token matches the shape of `param_type`: the variant, integer
+/// bit widths, fixed bytes length, fixed array length and tuple arity
+pub fn type_check(token: &Token, param_type: &ParamType) -> Result<(), Error> {
+    match (param_type, token) {
+        (ParamType::Address, Token::Address(_))
+        | (ParamType::Bool, Token::Bool(_))
+        | (ParamType::Bytes, Token::Bytes(_))
+        | (ParamType::String, Token::String(_)) => Ok(()),
+        (ParamType::Uint(bits), Token::Uint(value))
+        | (ParamType::Ufixed(bits, _), Token::Uint(value)) => {
+            if value.bits() > *bits {
+                return Err(Error::Overflow(format!(
+                    "Overflow value={}, type={}",
+                    value, param_type
+                )));
+            }
+            Ok(())
+        }
+        (ParamType::Int(bits), Token::Int(value))
+        | (ParamType::Fixed(bits, _), Token::Int(value)) => {
+            if !fits_signed(*value, *bits) {
+                return Err(Error::Overflow(format!(
+                    "Overflow value={:#x}, type={}",
+                    value, param_type
+                )));
+            }
+            Ok(())
+        }
+        (ParamType::FixedBytes(m), Token::FixedBytes(value_bytes)) => {
+            if value_bytes.len() != *m {
+                return Err(Error::InvalidLength {
+                    expected: *m,
+                    actual: value_bytes.len(),
+                });
+            }
+            Ok(())
+        }
+        (ParamType::Array(subtype), Token::Array(tokens)) => {
+            tokens.iter().try_for_each(|t| type_check(t, subtype))
+        }
+        (ParamType::FixedArray(subtype, len), Token::FixedArray(tokens)) => {
+            check_count(param_type, *len, tokens.len())?;
+            tokens.iter().try_for_each(|t| type_check(t, subtype))
+        }
+        (ParamType::Tuple(subtypes), Token::Tuple(tokens)) => {
+            check_count(param_type, subtypes.len(), tokens.len())?;
+            tokens
+                .iter()
+                .zip(subtypes)
+                .try_for_each(|(t, subtype)| type_check(t, subtype))
+        }
+        _ => Err(Error::InvalidValue(format!(
+            "Token mismatch: type={}, token={}",
+            param_type,
+            token.kind_name()
+        ))),
+    }
+}
+
+fn encode_word(value: U256) -> Bytes {
+    let mut buf = [0u8; WORD_SIZE];
+    value.to_big_endian(&mut buf);
+    buf.to_vec()
+}
+
+fn encode_padded(buf: &mut Bytes, value_bytes: &[u8]) {
+    buf.extend(value_bytes);
+    let padding_len = pad_to_word(value_bytes.len()) - value_bytes.len();
+    buf.extend(std::iter::repeat_n(0u8, padding_len));
+}
+
+/// Encode a token in place: the inline content of a static token, or the
+/// tail content of a dynamic token
+pub(crate) fn encode_token(token: &Token) -> Bytes {
+    encode_token_with(token, &EncodeOptions::default())
+}
+
+fn encode_token_with(token: &Token, options: &EncodeOptions) -> Bytes {
+    let encode_dynamic = |value_bytes: &[u8]| {
+        let mut buf = encode_usize(value_bytes.len());
+        if options.pad_dynamic {
+            encode_padded(&mut buf, value_bytes);
+        } else {
+            buf.extend_from_slice(value_bytes);
+        }
+        buf
+    };
+    match token {
+        Token::Address(address) => {
+            let mut buf = vec![0u8; 12];
+            buf.extend_from_slice(address.as_ref());
+            buf
+        }
+        Token::FixedBytes(value_bytes) => {
+            let mut buf = Vec::new();
+            encode_padded(&mut buf, value_bytes);
+            buf
+        }
+        Token::Bytes(value_bytes) => encode_dynamic(value_bytes),
+        Token::String(value) => encode_dynamic(value.as_bytes()),
+        Token::Int(value) | Token::Uint(value) => encode_word(*value),
+        Token::Bool(value) => encode_word(U256::from(*value as u64)),
+        Token::FixedArray(tokens) | Token::Tuple(tokens) => {
+            encode_token_sequence_with(tokens, options)
+        }
+        Token::Array(tokens) => {
+            let mut buf = encode_usize(tokens.len());
+            buf.extend(encode_token_sequence_with(tokens, options));
+            buf
+        }
+    }
+}
+
+/// Encode tokens with the head/tail layout
+pub(crate) fn encode_token_sequence(tokens: &[Token]) -> Bytes {
+    encode_token_sequence_with(tokens, &EncodeOptions::default())
+}
+
+pub(crate) fn encode_token_sequence_with(tokens: &[Token], options: &EncodeOptions) -> Bytes {
+    let encoded: Vec<(bool, Bytes)> = tokens
+        .iter()
+        .map(|token| (token.is_dynamic(), encode_token_with(token, options)))
+        .collect();
+    let head_words: usize = encoded
+        .iter()
+        .map(|(dynamic, value)| if *dynamic { 1 } else { value.len() / WORD_SIZE })
+        .sum();
+    layout_sequence(encoded, head_words)
+}
+
+/// Encode tokens with the head/tail layout, the typed counterpart of
+/// `Params::encode`
+///
+/// Every token is checked against its declared type with `type_check`
+/// first, so a `Token::Uint` for an `address` slot fails instead of being
+/// encoded as is.
+pub fn encode_tokens(types: &[ParamType], tokens: &[Token]) -> Result<Bytes, Error> {
+    if types.len() != tokens.len() {
+        return Err(Error::InvalidValue(format!(
+            "Token count mismatch: types={}, tokens={}",
+            types.len(),
+            tokens.len()
+        )));
+    }
+    for (token, param_type) in tokens.iter().zip(types) {
+        type_check(token, param_type)?;
+    }
+    encode_tokens_unchecked(tokens)
+}
+
+/// Encode tokens with the head/tail layout without checking them against
+/// any declared types, each token is encoded as the type it describes
+///
+/// Fails with `Error::InvalidLength` if a `Token::FixedBytes`, at any
+/// depth, is empty or holds more than 32 bytes.
+pub fn encode_tokens_unchecked(tokens: &[Token]) -> Result<Bytes, Error> {
+    check_fixed_bytes(tokens)?;
+    Ok(encode_token_sequence(tokens))
+}
+
+fn check_fixed_bytes(tokens: &[Token]) -> Result<(), Error> {
+    for token in tokens {
+        match token {
+            Token::FixedBytes(value_bytes) if value_bytes.is_empty() || value_bytes.len() > 32 => {
+                return Err(Error::InvalidLength {
+                    expected: if value_bytes.is_empty() { 1 } else { 32 },
+                    actual: value_bytes.len(),
+                })
+            }
+            Token::FixedArray(tokens) | Token::Array(tokens) | Token::Tuple(tokens) => {
+                check_fixed_bytes(tokens)?
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::str::FromStr;
+    use {DefaultTokenizer, ParamType, Tokenizer};
+
+    #[test]
+    fn test_encode_token_sequence() {
+        let types = vec![
+            ParamType::from_str("uint256").unwrap(),
+            ParamType::from_str("(string,bool)[]").unwrap(),
+        ];
+        let values = ["3", "[(a,true),(bc,false)]"];
+        let tokens: Vec<Token> = types
+            .iter()
+            .zip(values.iter())
+            .map(|(t, v)| DefaultTokenizer.tokenize(t, v).unwrap())
+            .collect();
+        let params: Vec<(ParamType, &str)> =
+            types.into_iter().zip(values.iter().cloned()).collect();
+        assert_eq!(Ok(encode_token_sequence(&tokens)), ::encode_params(&params));
+    }
+
+    #[test]
+    fn test_display_token() {
+        let minus_one = Token::Int(U256::max_value());
+        assert_eq!(minus_one.to_string(), "-1");
+        assert_eq!(Token::Int(U256::from(5)).to_string(), "5");
+        assert_eq!(Token::Uint(U256::max_value()).to_string(), U256::max_value().to_string());
+        let tuple = Token::Tuple(vec![
+            Token::Array(vec![minus_one.clone(), Token::Bool(true)]),
+            Token::Bytes(vec![0xde, 0xad]),
+        ]);
+        assert_eq!(tuple.to_string(), "([-1,true],0xdead)");
+        assert_eq!(
+            ::serde_json::to_string(&tuple).unwrap(),
+            r#"[["-1",true],"0xdead"]"#
+        );
+
+        let data = ::encode_params(&[(ParamType::Int(256), "-1")]).unwrap();
+        let tokens = ::decode_tokens(&[ParamType::Int(256)], &data).unwrap();
+        assert_eq!(tokens, vec![minus_one]);
+        assert_eq!(tokens[0].to_string(), "-1");
+        assert_eq!(::serde_json::to_string(&tokens).unwrap(), r#"["-1"]"#);
+    }
+
+    #[test]
+    fn test_encode_tokens_roundtrip() {
+        let types = [
+            ParamType::Address,
+            ParamType::from_str("int8").unwrap(),
+            ParamType::from_str("bytes4").unwrap(),
+            ParamType::from_str("(string,bool)[]").unwrap(),
+            ParamType::from_str("uint16[2]").unwrap(),
+        ];
+        let tokens = vec![
+            Token::Address(Address::from([0x11; 20])),
+            Token::Int(!U256::zero()),
+            Token::FixedBytes(vec![0xde, 0xad, 0xbe, 0xef]),
+            Token::Array(vec![
+                Token::Tuple(vec![Token::String("a".to_owned()), Token::Bool(true)]),
+                Token::Tuple(vec![Token::String("bc".to_owned()), Token::Bool(false)]),
+            ]),
+            Token::FixedArray(vec![Token::Uint(U256::from(1)), Token::Uint(U256::from(2))]),
+        ];
+        let data = encode_tokens(&types, &tokens).unwrap();
+        assert_eq!(::decode_tokens(&types, &data), Ok(tokens));
+    }
+
+    #[test]
+    fn test_type_check() {
+        let param_type = ParamType::from_str("(address,int8,bytes2,uint8[2])").unwrap();
+        let token = Token::Tuple(vec![
+            Token::Address(Address::zero()),
+            Token::Int(!U256::from(127)),
+            Token::FixedBytes(vec![1, 2]),
+            Token::FixedArray(vec![Token::Uint(U256::from(255)), Token::Uint(U256::zero())]),
+        ]);
+        assert_eq!(type_check(&token, &param_type), Ok(()));
+
+        let mismatches = [
+            ("address", Token::Uint(U256::one())),
+            ("uint8", Token::Uint(U256::from(256))),
+            ("int8", Token::Int(U256::from(128))),
+            ("int8", Token::Int(!U256::from(128))),
+            ("bytes2", Token::FixedBytes(vec![1])),
+            ("uint8[2]", Token::FixedArray(vec![Token::Uint(U256::one())])),
+            ("(bool,bool)", Token::Tuple(vec![Token::Bool(true)])),
+            ("bool[]", Token::Array(vec![Token::String("true".to_owned())])),
+        ];
+        for (type_str, token) in mismatches.iter() {
+            let param_type = ParamType::from_str(type_str).unwrap();
+            assert!(type_check(token, &param_type).is_err(), "{}", type_str);
+        }
+    }
+
+    #[test]
+    fn test_encode_tokens_type_check() {
+        let types = [ParamType::Address, ParamType::Uint(256)];
+        let tokens = [Token::Address(Address::zero()), Token::Uint(U256::one())];
+        assert_eq!(encode_tokens(&types, &tokens), encode_tokens_unchecked(&tokens));
+        assert!(encode_tokens(&types, &tokens[..1]).is_err());
+        assert!(encode_tokens(&types[1..], &tokens[..1]).is_err());
+        let tokens = [Token::Uint(U256::one()), Token::Uint(U256::one())];
+        assert!(encode_tokens(&types, &tokens).is_err());
+        assert!(encode_tokens_unchecked(&tokens).is_ok());
+    }
+
+    #[test]
+    fn test_encode_tokens_fixed_bytes_length() {
+        let tokens = [Token::Array(vec![Token::FixedBytes(vec![0; 33])])];
+        assert_eq!(
+            encode_tokens_unchecked(&tokens),
+            Err(Error::InvalidLength {
+                expected: 32,
+                actual: 33
+            })
+        );
+        let tokens = [Token::Tuple(vec![Token::FixedArray(vec![Token::FixedBytes(vec![])])])];
+        assert_eq!(
+            encode_tokens_unchecked(&tokens),
+            Err(Error::InvalidLength {
+                expected: 1,
+                actual: 0
+            })
+        );
+        assert!(encode_tokens_unchecked(&[Token::FixedBytes(vec![0; 32])]).is_ok());
+    }
+}
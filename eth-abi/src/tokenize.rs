@@ -0,0 +1,584 @@
+//! Value string parsing into tokens
+
+use ethereum_types::{Address, U256};
+use hex::FromHex;
+use serde_json::Value;
+
+use super::{pad_to_word, parse_units, split_values, units, Bytes, Error, ParamType, Token};
+
+/// Tokenize a value string with the rules of `DefaultTokenizer`
+pub fn tokenize(param_type: &ParamType, value_str: &str) -> Result<Token, Error> {
+    DefaultTokenizer.tokenize(param_type, value_str)
+}
+
+/// Largest integer a JSON number (an `f64`) holds exactly, `2^53 - 1`
+const MAX_SAFE_JSON_INTEGER: u64 = (1 << 53) - 1;
+
+/// Tokenize a JSON value: strings follow `tokenize`, JSON arrays hold
+/// array elements or tuple members, and numbers are accepted for integers
+/// up to `2^53 - 1` in magnitude. Larger integers must be passed as decimal
+/// or hex strings, since JSON numbers beyond that may have lost precision.
+pub fn tokenize_json(param_type: &ParamType, value: &Value) -> Result<Token, Error> {
+    let tokenize_all = |subtypes: &mut dyn Iterator<Item = &ParamType>, values: &[Value]| {
+        subtypes
+            .zip(values)
+            .map(|(t, v)| tokenize_json(t, v))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let check_len = |expected: usize, actual: usize| {
+        if expected == actual {
+            Ok(())
+        } else {
+            Err(Error::InvalidLength { expected, actual })
+        }
+    };
+    match (param_type, value) {
+        (_, Value::String(value_str)) => tokenize(param_type, value_str),
+        (_, Value::Bool(value)) => tokenize_scalar(param_type, &value.to_string()),
+        (_, Value::Number(number)) => {
+            let safe = number
+                .as_u64()
+                .or_else(|| number.as_i64().map(|n| n.unsigned_abs()))
+                .is_some_and(|n| n <= MAX_SAFE_JSON_INTEGER);
+            if !safe {
+                return Err(Error::InvalidValue(format!(
+                    "Invalid JSON number={}, not a safe integer, pass it as a string",
+                    number
+                )));
+            }
+            tokenize_scalar(param_type, &number.to_string())
+        }
+        (ParamType::Array(subtype), Value::Array(values)) => {
+            tokenize_all(&mut std::iter::repeat(&**subtype), values).map(Token::Array)
+        }
+        (ParamType::FixedArray(subtype, len), Value::Array(values)) => {
+            check_len(*len, values.len())?;
+            tokenize_all(&mut std::iter::repeat(&**subtype), values).map(Token::FixedArray)
+        }
+        (ParamType::Tuple(subtypes), Value::Array(values)) => {
+            check_len(subtypes.len(), values.len())?;
+            tokenize_all(&mut subtypes.iter(), values).map(Token::Tuple)
+        }
+        _ => Err(Error::InvalidValue(format!(
+            "Invalid JSON value={} for type={}",
+            value, param_type
+        ))),
+    }
+}
+
+/// Parse `0x` hex or raw text bytes, returning the length and the bytes
+/// right padded to a multiple of 32
+pub(crate) fn parse_bytes(value_str: &str) -> Result<(usize, Bytes), Error> {
+    let mut value_bytes = if let Some(hex_str) = value_str.strip_prefix("0x") {
+        hex_str
+            .from_hex()
+            .map_err(|e| Error::InvalidValue(format!("Invalid hex value={}, {:?}", value_str, e)))?
+    } else {
+        value_str.as_bytes().to_vec()
+    };
+    let len = value_bytes.len();
+    value_bytes.resize(pad_to_word(len), 0);
+    Ok((len, value_bytes))
+}
+
+fn parse_dec(digits: &str, value_str: &str) -> Result<U256, Error> {
+    U256::from_dec_str(digits).map_err(|e| {
+        if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+            Error::Overflow(format!("Overflow value={}, more than 256 bits", value_str))
+        } else {
+            Error::InvalidValue(format!("Invalid decimal value={}, {:?}", value_str, e))
+        }
+    })
+}
+
+/// Parse a decimal integer, optionally followed by a unit, e.g. `1.5 ether`
+fn parse_amount(digits: &str, value_str: &str) -> Result<U256, Error> {
+    match units::split_unit(digits) {
+        Some((amount, decimals)) => parse_units(amount, decimals),
+        None => parse_dec(digits, value_str),
+    }
+}
+
+/// Parse a decimal or `0x` hex integer of at most `bits` bits into its two's
+/// complement word
+///
+/// Signed decimal values must be within `-2^(bits-1)..2^(bits-1)`, signed
+/// hex values are taken as a `bits` wide two's complement bit pattern, e.g.
+/// `0xff` is `-1` for `int8`.
+fn parse_int(param_type: &ParamType, bits: usize, value_str: &str) -> Result<U256, Error> {
+    let signed = matches!(param_type, ParamType::Int(_));
+    let overflow = || {
+        Error::Overflow(format!(
+            "Overflow value={}, type={:?}",
+            value_str, param_type
+        ))
+    };
+    if let Some(hex_str) = value_str.strip_prefix("0x") {
+        let value_bytes = hex_str.from_hex().map_err(|e| {
+            Error::InvalidValue(format!("Invalid hex value={}, {:?}", value_str, e))
+        })?;
+        if value_bytes.len() > 32 {
+            return Err(overflow());
+        }
+        let value = if value_bytes.is_empty() {
+            U256::zero()
+        } else {
+            U256::from(value_bytes.as_slice())
+        };
+        if bits == 256 {
+            return Ok(value);
+        }
+        if value.bits() > bits {
+            return Err(overflow());
+        }
+        // Sign extend the `bits` wide pattern to a full word
+        return Ok(if signed && value.bit(bits - 1) {
+            value | (U256::max_value() << bits)
+        } else {
+            value
+        });
+    }
+    let (negative, abs_str) = split_sign(param_type, signed, value_str)?;
+    signed_word(param_type, bits, negative, parse_amount(abs_str, value_str)?, value_str)
+}
+
+/// Split a leading `-`, which only signed types accept
+fn split_sign<'a>(
+    param_type: &ParamType,
+    signed: bool,
+    value_str: &'a str,
+) -> Result<(bool, &'a str), Error> {
+    match value_str.strip_prefix('-') {
+        Some(_) if !signed => Err(Error::InvalidValue(format!(
+            "Invalid value={} for type={:?}",
+            value_str, param_type
+        ))),
+        Some(abs_str) => Ok((true, abs_str)),
+        None => Ok((false, value_str)),
+    }
+}
+
+/// Range check a magnitude against a `bits` wide type and turn it into its
+/// two's complement word
+fn signed_word(
+    param_type: &ParamType,
+    bits: usize,
+    negative: bool,
+    value: U256,
+    value_str: &str,
+) -> Result<U256, Error> {
+    let signed = matches!(param_type, ParamType::Int(_) | ParamType::Fixed(_, _));
+    let magnitude_bits = if signed { bits - 1 } else { bits };
+    if magnitude_bits < 256 {
+        let limit = U256::one() << magnitude_bits;
+        // The negative range reaches one further, down to -2^(bits-1)
+        if value > limit || (value == limit && !negative) {
+            return Err(Error::Overflow(format!(
+                "Overflow value={}, type={:?}",
+                value_str, param_type
+            )));
+        }
+    }
+    Ok(if negative {
+        (!value) + U256::one()
+    } else {
+        value
+    })
+}
+
+/// Parse a decimal like `12.345` into its `bits` wide word scaled by
+/// `10^decimals`, e.g. `fixed128x18`
+fn parse_fixed(
+    param_type: &ParamType,
+    bits: usize,
+    decimals: usize,
+    value_str: &str,
+) -> Result<U256, Error> {
+    let signed = matches!(param_type, ParamType::Fixed(_, _));
+    let (negative, abs_str) = split_sign(param_type, signed, value_str)?;
+    if !abs_str.bytes().all(|b| b.is_ascii_digit() || b == b'.') {
+        return Err(Error::InvalidValue(format!(
+            "Invalid decimal value={} for type={}",
+            value_str, param_type
+        )));
+    }
+    let value = parse_units(abs_str, decimals as u32)?;
+    signed_word(param_type, bits, negative, value, value_str)
+}
+
+/// Strip a trailing `u<M>`/`i<M>` type suffix, e.g. `100u256` or `42i8`,
+/// which must match the signedness and width of `param_type`
+fn strip_int_suffix<'a>(param_type: &ParamType, value_str: &'a str) -> Result<&'a str, Error> {
+    let digits_start = value_str.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    if digits_start < 2 || digits_start == value_str.len() {
+        return Ok(value_str);
+    }
+    let (value, suffix) = value_str.split_at(digits_start - 1);
+    let suffix_type = match suffix.as_bytes()[0] {
+        b'u' => ParamType::Uint(suffix[1..].parse().unwrap_or(0)),
+        b'i' => ParamType::Int(suffix[1..].parse().unwrap_or(0)),
+        _ => return Ok(value_str),
+    };
+    if suffix_type != *param_type {
+        return Err(Error::InvalidValue(format!(
+            "Invalid value={}, suffix {} does not match type={}",
+            value_str, suffix, param_type
+        )));
+    }
+    Ok(value)
+}
+
+/// Tokenize a value of a type other than an array or tuple
+pub(crate) fn tokenize_scalar(param_type: &ParamType, value_str: &str) -> Result<Token, Error> {
+    match param_type {
+        ParamType::Address => {
+            let hex_str = value_str.strip_prefix("0x").unwrap_or(value_str);
+            if value_str.contains('.') {
+                return Err(Error::InvalidValue(format!(
+                    "Invalid address value={}, ENS names are not resolved, use the 0x hex address",
+                    value_str
+                )));
+            }
+            let value_bytes = hex_str.from_hex().map_err(|e| {
+                Error::InvalidValue(format!("Invalid address value={}, {:?}", value_str, e))
+            })?;
+            if value_bytes.len() != 20 {
+                return Err(Error::InvalidLength {
+                    expected: 20,
+                    actual: value_bytes.len(),
+                });
+            }
+            let mut address = [0u8; 20];
+            address.copy_from_slice(&value_bytes);
+            Ok(Token::Address(Address::from(address)))
+        }
+        // Numbers and bools never contain whitespace, trim like type strings
+        ParamType::Uint(m) => {
+            let value_str = strip_int_suffix(param_type, value_str.trim())?;
+            parse_int(param_type, *m, value_str).map(Token::Uint)
+        }
+        ParamType::Int(m) => {
+            let value_str = strip_int_suffix(param_type, value_str.trim())?;
+            parse_int(param_type, *m, value_str).map(Token::Int)
+        }
+        ParamType::Ufixed(m, n) => {
+            parse_fixed(param_type, *m, *n, value_str.trim()).map(Token::Uint)
+        }
+        ParamType::Fixed(m, n) => {
+            parse_fixed(param_type, *m, *n, value_str.trim()).map(Token::Int)
+        }
+        ParamType::Bool => match value_str.trim() {
+            "true" => Ok(Token::Bool(true)),
+            "false" => Ok(Token::Bool(false)),
+            _ => Err(Error::InvalidValue(format!(
+                "Invalid value for bool: {}",
+                value_str
+            ))),
+        },
+        ParamType::FixedBytes(m) => {
+            if *m == 0 || *m > 32 {
+                return Err(Error::InvalidType {
+                    message: format!("Invalid param type: {:?}", param_type),
+                    position: 0,
+                });
+            }
+            let (len, mut value_bytes) = parse_bytes(value_str)?;
+            if len > *m {
+                return Err(Error::InvalidValue(format!(
+                    "Error value length: value={}",
+                    value_str
+                )));
+            }
+            // Right padded to exactly `m` bytes
+            value_bytes.truncate(*m);
+            Ok(Token::FixedBytes(value_bytes))
+        }
+        ParamType::Bytes => {
+            let (len, mut value_bytes) = parse_bytes(value_str)?;
+            if len > value_str.chars().count() {
+                return Err(Error::InvalidValue(format!(
+                    "Value is not bytes: {}",
+                    value_str
+                )));
+            }
+            value_bytes.truncate(len);
+            Ok(Token::Bytes(value_bytes))
+        }
+        ParamType::String => {
+            let (len, mut value_bytes) = parse_bytes(value_str)?;
+            value_bytes.truncate(len);
+            String::from_utf8(value_bytes)
+                .map(Token::String)
+                .map_err(|e| Error::InvalidValue(format!("Invalid utf8 string: {:?}", e)))
+        }
+        _ => Err(Error::Unsupported(format!(
+            "Cannot tokenize type: {:?}",
+            param_type
+        ))),
+    }
+}
+
+/// Turns value strings into tokens
+pub trait Tokenizer {
+    /// Tokenize `value` as a value of `param_type`
+    fn tokenize(&self, param_type: &ParamType, value: &str) -> Result<Token, Error>;
+}
+
+/// Tokenizer for the value string syntax: decimal or `0x` hex integers,
+/// optionally with a matching type suffix like `42i8`, `true`/`false`, `0x`
+/// hex or raw text bytes, `[a,b]` arrays and `(a,b)` tuples
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultTokenizer;
+
+impl DefaultTokenizer {
+    /// Tokenize with the default rules, array and tuple elements are passed
+    /// back to `tokenizer`. Custom tokenizers can fall back to this to keep
+    /// their rules applied inside nested values.
+    pub fn tokenize_nested(
+        tokenizer: &dyn Tokenizer,
+        param_type: &ParamType,
+        value: &str,
+    ) -> Result<Token, Error> {
+        match param_type {
+            ParamType::Array(subtype) => split_values(value, '[', ']')?
+                .into_iter()
+                .map(|v| tokenizer.tokenize(subtype, v))
+                .collect::<Result<_, _>>()
+                .map(Token::Array),
+            ParamType::FixedArray(subtype, len) => {
+                let values = split_values(value, '[', ']')?;
+                if values.len() != *len {
+                    return Err(Error::InvalidLength {
+                        expected: *len,
+                        actual: values.len(),
+                    });
+                }
+                values
+                    .into_iter()
+                    .map(|v| tokenizer.tokenize(subtype, v))
+                    .collect::<Result<_, _>>()
+                    .map(Token::FixedArray)
+            }
+            ParamType::Tuple(subtypes) => {
+                let values = split_values(value, '(', ')')?;
+                if values.len() != subtypes.len() {
+                    return Err(Error::InvalidLength {
+                        expected: subtypes.len(),
+                        actual: values.len(),
+                    });
+                }
+                subtypes
+                    .iter()
+                    .zip(values)
+                    .map(|(t, v)| tokenizer.tokenize(t, v))
+                    .collect::<Result<_, _>>()
+                    .map(Token::Tuple)
+            }
+            _ => tokenize_scalar(param_type, value),
+        }
+    }
+}
+
+impl Tokenizer for DefaultTokenizer {
+    fn tokenize(&self, param_type: &ParamType, value: &str) -> Result<Token, Error> {
+        Self::tokenize_nested(self, param_type, value)
+    }
+}
+
+/// Tokenizer that also accepts `0b` binary and `0o` octal integer literals,
+/// e.g. `0b1010` or `-0o17`, with the usual range checks. Everything else
+/// follows `DefaultTokenizer`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RadixTokenizer;
+
+/// Parse the digits of a `0b`/`0o` literal
+fn parse_radix(digits: &str, radix: u32, value_str: &str) -> Result<U256, Error> {
+    if digits.is_empty() {
+        return Err(Error::InvalidValue(format!("Invalid value={}", value_str)));
+    }
+    digits.chars().try_fold(U256::zero(), |value, c| {
+        let digit = c
+            .to_digit(radix)
+            .ok_or_else(|| Error::InvalidValue(format!("Invalid value={}", value_str)))?;
+        value
+            .checked_mul(U256::from(radix))
+            .and_then(|value| value.checked_add(U256::from(digit)))
+            .ok_or_else(|| Error::Overflow(format!("Overflow value={}", value_str)))
+    })
+}
+
+impl Tokenizer for RadixTokenizer {
+    fn tokenize(&self, param_type: &ParamType, value: &str) -> Result<Token, Error> {
+        if let ParamType::Int(_) | ParamType::Uint(_) = param_type {
+            let (sign, abs_str) = match value.strip_prefix('-') {
+                Some(abs_str) => ("-", abs_str),
+                None => ("", value),
+            };
+            let radix = if abs_str.starts_with("0b") {
+                Some(2)
+            } else if abs_str.starts_with("0o") {
+                Some(8)
+            } else {
+                None
+            };
+            if let Some(radix) = radix {
+                let abs_value = parse_radix(&abs_str[2..], radix, value)?;
+                let dec_str = format!("{}{}", sign, abs_value);
+                return DefaultTokenizer::tokenize_nested(self, param_type, &dec_str);
+            }
+        }
+        DefaultTokenizer::tokenize_nested(self, param_type, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_tokenize_scalars() {
+        let address = "0x5b38da6a701c568545dcfcb03fcb875f56beddc4";
+        let mut address_bytes = [0u8; 20];
+        let value_bytes: Vec<u8> = address[2..].from_hex().unwrap();
+        address_bytes.copy_from_slice(&value_bytes);
+        assert_eq!(
+            tokenize(&ParamType::Address, address),
+            Ok(Token::Address(Address::from(address_bytes)))
+        );
+        assert_eq!(
+            tokenize(&ParamType::Uint(256), "0xff"),
+            Ok(Token::Uint(U256::from(255)))
+        );
+        assert_eq!(
+            tokenize(&ParamType::Int(8), "-2"),
+            Ok(Token::Int(U256::max_value() - U256::one()))
+        );
+        assert_eq!(tokenize(&ParamType::Bool, "false"), Ok(Token::Bool(false)));
+        assert_eq!(
+            tokenize(&ParamType::FixedBytes(3), "0xab"),
+            Ok(Token::FixedBytes(vec![0xab, 0, 0]))
+        );
+        assert_eq!(
+            tokenize(&ParamType::Bytes, "0xdead"),
+            Ok(Token::Bytes(vec![0xde, 0xad]))
+        );
+        assert_eq!(
+            tokenize(&ParamType::String, "0x616263"),
+            Ok(Token::String("abc".to_owned()))
+        );
+        assert!(tokenize(&ParamType::String, "0xff").is_err());
+        assert!(tokenize(&ParamType::Uint(8), "256").is_err());
+        assert!(tokenize(&ParamType::FixedBytes(1), "0xabcd").is_err());
+    }
+
+    #[test]
+    fn test_tokenize_int_suffix() {
+        assert_eq!(
+            tokenize(&ParamType::Uint(256), "100u256"),
+            Ok(Token::Uint(U256::from(100)))
+        );
+        assert_eq!(
+            tokenize(&ParamType::Int(8), "-42i8"),
+            tokenize(&ParamType::Int(8), "-42")
+        );
+        assert_eq!(
+            tokenize(&ParamType::Uint(8), "0xffu8"),
+            Ok(Token::Uint(U256::from(255)))
+        );
+        match tokenize(&ParamType::Uint(256), "42i8") {
+            Err(Error::InvalidValue(message)) => assert_eq!(
+                message,
+                "Invalid value=42i8, suffix i8 does not match type=uint256"
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(tokenize(&ParamType::Uint(256), "100u8").is_err());
+        assert!(tokenize(&ParamType::Uint(256), "u256").is_err());
+    }
+
+    #[test]
+    fn test_tokenize_json() {
+        let uint = ParamType::Uint(256);
+        let json = |s: &str| s.parse::<Value>().unwrap();
+        assert_eq!(tokenize_json(&uint, &json("42")), Ok(Token::Uint(U256::from(42))));
+        assert_eq!(
+            tokenize_json(&ParamType::Int(8), &json("-42")),
+            tokenize(&ParamType::Int(8), "-42")
+        );
+        let large = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+        assert_eq!(
+            tokenize_json(&uint, &Value::String(large.to_owned())),
+            Ok(Token::Uint(U256::max_value()))
+        );
+        assert_eq!(
+            tokenize_json(&uint, &json(r#""0xff""#)),
+            Ok(Token::Uint(U256::from(255)))
+        );
+        assert!(tokenize_json(&uint, &json("9007199254740991")).is_ok());
+        assert!(tokenize_json(&uint, &json("9007199254740992")).is_err());
+        assert!(tokenize_json(&uint, &json("1e30")).is_err());
+        assert!(tokenize_json(&uint, &json("1.5")).is_err());
+
+        let param_type = ParamType::from_str("(uint256[],bool,string)").unwrap();
+        assert_eq!(
+            tokenize_json(&param_type, &json(r#"[[1, "2"], true, "abc"]"#)),
+            tokenize(&param_type, "([1,2],true,abc)")
+        );
+        assert!(tokenize_json(&param_type, &json("[[1], true]")).is_err());
+        assert!(tokenize_json(&ParamType::Bool, &json("null")).is_err());
+    }
+
+    #[test]
+    fn test_default_tokenizer() {
+        let tokenizer = DefaultTokenizer;
+        let param_type = ParamType::from_str("(uint8,bool,string,bytes2[])").unwrap();
+        assert_eq!(
+            tokenizer.tokenize(&param_type, "(7,true,abc,[0x0102])"),
+            Ok(Token::Tuple(vec![
+                Token::Uint(U256::from(7)),
+                Token::Bool(true),
+                Token::String("abc".to_owned()),
+                Token::Array(vec![Token::FixedBytes(vec![1, 2])]),
+            ]))
+        );
+        assert_eq!(
+            tokenizer.tokenize(&ParamType::Int(256), "-1"),
+            Ok(Token::Int(U256::max_value()))
+        );
+        assert!(tokenizer.tokenize(&ParamType::Bool, "yes").is_err());
+    }
+
+    #[test]
+    fn test_radix_tokenizer() {
+        let tokenizer = RadixTokenizer;
+        assert_eq!(
+            tokenizer.tokenize(&ParamType::Uint(8), "0b1010"),
+            Ok(Token::Uint(U256::from(10)))
+        );
+        assert_eq!(
+            tokenizer.tokenize(&ParamType::Uint(256), "0o17"),
+            Ok(Token::Uint(U256::from(15)))
+        );
+        assert_eq!(
+            tokenizer.tokenize(&ParamType::Int(8), "-0b1"),
+            Ok(Token::Int(U256::max_value()))
+        );
+        assert_eq!(
+            tokenizer.tokenize(&ParamType::from_str("uint8[]").unwrap(), "[0b1,0x02,3]"),
+            Ok(Token::Array(vec![
+                Token::Uint(U256::from(1)),
+                Token::Uint(U256::from(2)),
+                Token::Uint(U256::from(3)),
+            ]))
+        );
+        match tokenizer.tokenize(&ParamType::Uint(8), "0o400") {
+            Err(Error::Overflow(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(tokenizer.tokenize(&ParamType::Uint(8), "0b102").is_err());
+        assert!(tokenizer.tokenize(&ParamType::Uint(8), "0b").is_err());
+        assert!(DefaultTokenizer.tokenize(&ParamType::Uint(8), "0b1010").is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! Token amount formatting and parsing
+
+use ethereum_types::U256;
+
+use super::Error;
+
+/// Format `value` in base units as a decimal amount with `decimals`
+/// fractional digits, trailing fractional zeros are trimmed, e.g.
+/// `1500000000000000000` with 18 decimals is `1.5`
+pub fn format_units(value: U256, decimals: u32) -> String {
+    let decimals = decimals as usize;
+    let digits = format!("{}", value);
+    if decimals == 0 {
+        return digits;
+    }
+    let digits = if digits.len() <= decimals {
+        "0".repeat(decimals - digits.len() + 1) + &digits
+    } else {
+        digits
+    };
+    let (int_part, frac_part) = digits.split_at(digits.len() - decimals);
+    let frac_part = frac_part.trim_end_matches('0');
+    if frac_part.is_empty() {
+        int_part.to_owned()
+    } else {
+        format!("{}.{}", int_part, frac_part)
+    }
+}
+
+/// Parse a decimal amount into base units with `decimals` fractional
+/// digits, e.g. `1.5` with 18 decimals is `1500000000000000000`. More
+/// fractional digits than `decimals` is an error rather than rounded.
+pub fn parse_units(amount: &str, decimals: u32) -> Result<U256, Error> {
+    let (int_part, frac_part) = match amount.find('.') {
+        Some(pos) => (&amount[..pos], &amount[pos + 1..]),
+        None => (amount, ""),
+    };
+    if frac_part.len() > decimals as usize {
+        return Err(Error::InvalidValue(format!(
+            "Invalid amount={}, more than {} decimals",
+            amount, decimals
+        )));
+    }
+    let digits = format!(
+        "{}{}{}",
+        int_part,
+        frac_part,
+        "0".repeat(decimals as usize - frac_part.len())
+    );
+    if int_part.is_empty() && frac_part.is_empty() {
+        return Err(Error::InvalidValue(format!("Invalid amount={}", amount)));
+    }
+    U256::from_dec_str(&digits).map_err(|e| {
+        if digits.bytes().all(|b| b.is_ascii_digit()) {
+            Error::Overflow(format!("Overflow amount={}, more than 256 bits", amount))
+        } else {
+            Error::InvalidValue(format!("Invalid amount={}, {:?}", amount, e))
+        }
+    })
+}
+
+/// Split `<amount> <unit>` into the amount and the decimals of the unit,
+/// `None` if there is no known unit suffix
+pub(crate) fn split_unit(value_str: &str) -> Option<(&str, u32)> {
+    let mut parts = value_str.split_whitespace();
+    let (amount, unit) = (parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    let decimals = match unit {
+        "wei" => 0,
+        "gwei" => 9,
+        "ether" => 18,
+        _ => return None,
+    };
+    Some((amount, decimals))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_format_units() {
+        let ether = U256::from_dec_str("1500000000000000000").unwrap();
+        assert_eq!(format_units(ether, 18), "1.5");
+        assert_eq!(format_units(U256::exp10(18), 18), "1");
+        assert_eq!(format_units(U256::from(1), 18), "0.000000000000000001");
+        assert_eq!(format_units(U256::from(2_500_000u64), 6), "2.5");
+        assert_eq!(format_units(U256::from(123_456_789u64), 6), "123.456789");
+        assert_eq!(format_units(U256::from(0), 6), "0");
+        assert_eq!(format_units(U256::max_value(), 0), format!("{}", U256::max_value()));
+    }
+
+    #[test]
+    fn test_parse_units() {
+        assert_eq!(
+            parse_units("1.5", 18),
+            Ok(U256::from_dec_str("1500000000000000000").unwrap())
+        );
+        assert_eq!(parse_units("2", 6), Ok(U256::from(2_000_000u64)));
+        assert_eq!(parse_units("0.000001", 6), Ok(U256::from(1)));
+        assert_eq!(parse_units(".5", 1), Ok(U256::from(5)));
+        assert_eq!(
+            parse_units("1.0000001", 6),
+            Err(Error::InvalidValue(
+                "Invalid amount=1.0000001, more than 6 decimals".to_owned()
+            ))
+        );
+        assert!(parse_units(".", 6).is_err());
+        assert!(parse_units("1.5x", 6).is_err());
+        match parse_units("1", 78) {
+            Err(Error::Overflow(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_split_unit() {
+        assert_eq!(split_unit("1.5 ether"), Some(("1.5", 18)));
+        assert_eq!(split_unit("30 gwei"), Some(("30", 9)));
+        assert_eq!(split_unit("30"), None);
+        assert_eq!(split_unit("30 satoshi"), None);
+    }
+}
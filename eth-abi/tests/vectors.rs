@@ -0,0 +1,382 @@
+//! Reference encoding vectors from the Solidity ABI spec and `ethabi`
+
+extern crate eth_abi;
+extern crate rustc_hex as hex;
+
+use eth_abi::{decode, encode_function_call, encode_params, parse_function, ParamType, Params};
+use hex::FromHex;
+use std::str::FromStr;
+
+/// (signature, values, expected calldata)
+const VECTORS: &[(&str, &[&str], &str)] = &[
+    // Solidity ABI spec examples
+    (
+        "baz(uint32,bool)",
+        &["69", "true"],
+        "cdcd77c0\
+         0000000000000000000000000000000000000000000000000000000000000045\
+         0000000000000000000000000000000000000000000000000000000000000001",
+    ),
+    (
+        "bar(bytes3[2])",
+        &["[abc,def]"],
+        "fce353f6\
+         6162630000000000000000000000000000000000000000000000000000000000\
+         6465660000000000000000000000000000000000000000000000000000000000",
+    ),
+    (
+        "sam(bytes,bool,uint256[])",
+        &["dave", "true", "[1,2,3]"],
+        "a5643bf2\
+         0000000000000000000000000000000000000000000000000000000000000060\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         00000000000000000000000000000000000000000000000000000000000000a0\
+         0000000000000000000000000000000000000000000000000000000000000004\
+         6461766500000000000000000000000000000000000000000000000000000000\
+         0000000000000000000000000000000000000000000000000000000000000003\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000003",
+    ),
+    (
+        "f(uint256,uint32[],bytes10,bytes)",
+        &["0x0123", "[0x0456,0x0789]", "1234567890", "Hello, world!"],
+        "8be65246\
+         0000000000000000000000000000000000000000000000000000000000000123\
+         0000000000000000000000000000000000000000000000000000000000000080\
+         3132333435363738393000000000000000000000000000000000000000000000\
+         00000000000000000000000000000000000000000000000000000000000000e0\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000456\
+         0000000000000000000000000000000000000000000000000000000000000789\
+         000000000000000000000000000000000000000000000000000000000000000d\
+         48656c6c6f2c20776f726c642100000000000000000000000000000000000000",
+    ),
+    (
+        "g(uint256[][],string[])",
+        &["[[1,2],[3]]", "[one,two,three]"],
+        "2289b18c\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000140\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         00000000000000000000000000000000000000000000000000000000000000a0\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000003\
+         0000000000000000000000000000000000000000000000000000000000000003\
+         0000000000000000000000000000000000000000000000000000000000000060\
+         00000000000000000000000000000000000000000000000000000000000000a0\
+         00000000000000000000000000000000000000000000000000000000000000e0\
+         0000000000000000000000000000000000000000000000000000000000000003\
+         6f6e650000000000000000000000000000000000000000000000000000000000\
+         0000000000000000000000000000000000000000000000000000000000000003\
+         74776f0000000000000000000000000000000000000000000000000000000000\
+         0000000000000000000000000000000000000000000000000000000000000005\
+         7468726565000000000000000000000000000000000000000000000000000000",
+    ),
+    // Nested tuples and mixed static/dynamic lists, cross-checked with ethabi
+    (
+        "h((uint256,string),bool)",
+        &["(1,hello)", "true"],
+        "df5a9052\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000005\
+         68656c6c6f000000000000000000000000000000000000000000000000000000",
+    ),
+    (
+        "k((uint256,(bool,string))[],address)",
+        &["[(1,(true,one)),(2,(false,two))]", "0x5b38da6a701c568545dcfcb03fcb875f56beddc4"],
+        "c86e105f\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000005b38da6a701c568545dcfcb03fcb875f56beddc4\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000100\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000003\
+         6f6e650000000000000000000000000000000000000000000000000000000000\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000000\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000003\
+         74776f0000000000000000000000000000000000000000000000000000000000",
+    ),
+    (
+        "m(string,uint256[],(address,bool))",
+        &["hello", "[7,8]", "(0x5b38da6a701c568545dcfcb03fcb875f56beddc4,true)"],
+        "b0cea6da\
+         0000000000000000000000000000000000000000000000000000000000000080\
+         00000000000000000000000000000000000000000000000000000000000000c0\
+         0000000000000000000000005b38da6a701c568545dcfcb03fcb875f56beddc4\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000005\
+         68656c6c6f000000000000000000000000000000000000000000000000000000\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000007\
+         0000000000000000000000000000000000000000000000000000000000000008",
+    ),
+    (
+        "n((uint256,uint256),uint8)",
+        &["(1,2)", "3"],
+        "dff52d54\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000003",
+    ),
+    (
+        "p(string[2],uint256)",
+        &["[a,bc]", "9"],
+        "d311a649\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000009\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000080\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         6100000000000000000000000000000000000000000000000000000000000000\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         6263000000000000000000000000000000000000000000000000000000000000",
+    ),
+    (
+        "q((uint256[],string)[2])",
+        &["[([1],x),([],yz)]"],
+        "c08d29ad\
+         0000000000000000000000000000000000000000000000000000000000000020\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000100\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000080\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         7800000000000000000000000000000000000000000000000000000000000000\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000060\
+         0000000000000000000000000000000000000000000000000000000000000000\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         797a000000000000000000000000000000000000000000000000000000000000",
+    ),
+    // Acceptance vectors for whole calls mixing nested dynamic and static
+    // values, as `abi.encodeWithSignature` in solc produces them; the
+    // expected calldata was generated with ethabi 18
+    (
+        "f(string,uint256[],(address,bool))",
+        &["hello", "[1,2,3]", "(0x8ba1f109551bd432803012645ac136ddd64dba72,true)"],
+        "812409db\
+         0000000000000000000000000000000000000000000000000000000000000080\
+         00000000000000000000000000000000000000000000000000000000000000c0\
+         0000000000000000000000008ba1f109551bd432803012645ac136ddd64dba72\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000005\
+         68656c6c6f000000000000000000000000000000000000000000000000000000\
+         0000000000000000000000000000000000000000000000000000000000000003\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000003",
+    ),
+    (
+        "setPerson((string,uint256,address[]))",
+        &["(alice,30,[0x8ba1f109551bd432803012645ac136ddd64dba72,0xab5801a7d398351b8be11c439e05c5b3259aec9b])"],
+        "9f174b02\
+         0000000000000000000000000000000000000000000000000000000000000020\
+         0000000000000000000000000000000000000000000000000000000000000060\
+         000000000000000000000000000000000000000000000000000000000000001e\
+         00000000000000000000000000000000000000000000000000000000000000a0\
+         0000000000000000000000000000000000000000000000000000000000000005\
+         616c696365000000000000000000000000000000000000000000000000000000\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000008ba1f109551bd432803012645ac136ddd64dba72\
+         000000000000000000000000ab5801a7d398351b8be11c439e05c5b3259aec9b",
+    ),
+    (
+        "r(int8,bytes,uint8[2],string,bool)",
+        &["-1", "0xdeadbeef", "[1,2]", "hi", "false"],
+        "6e9d3d4e\
+         ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff\
+         00000000000000000000000000000000000000000000000000000000000000c0\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000100\
+         0000000000000000000000000000000000000000000000000000000000000000\
+         0000000000000000000000000000000000000000000000000000000000000004\
+         deadbeef00000000000000000000000000000000000000000000000000000000\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         6869000000000000000000000000000000000000000000000000000000000000",
+    ),
+];
+
+/// (type, value, expected `abi.encode(value)`) for arrays and tuples nested
+/// in each other, generated with ethabi 18
+const NESTED_VECTORS: &[(&str, &str, &str)] = &[
+    (
+        "(uint256,address)[][3]",
+        "[[(1,0x5b38da6a701c568545dcfcb03fcb875f56beddc4)],[],[(2,0xab5801a7d398351b8be11c439e05c5b3259aec9b),(3,0x5b38da6a701c568545dcfcb03fcb875f56beddc4)]]",
+        "0000000000000000000000000000000000000000000000000000000000000020\
+         0000000000000000000000000000000000000000000000000000000000000060\
+         00000000000000000000000000000000000000000000000000000000000000c0\
+         00000000000000000000000000000000000000000000000000000000000000e0\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000005b38da6a701c568545dcfcb03fcb875f56beddc4\
+         0000000000000000000000000000000000000000000000000000000000000000\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         000000000000000000000000ab5801a7d398351b8be11c439e05c5b3259aec9b\
+         0000000000000000000000000000000000000000000000000000000000000003\
+         0000000000000000000000005b38da6a701c568545dcfcb03fcb875f56beddc4",
+    ),
+    (
+        "(uint256[],bool)[2]",
+        "[([1,2],true),([],false)]",
+        "0000000000000000000000000000000000000000000000000000000000000020\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         00000000000000000000000000000000000000000000000000000000000000e0\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000000\
+         0000000000000000000000000000000000000000000000000000000000000000",
+    ),
+    (
+        "uint8[2][][2]",
+        "[[[1,2]],[[3,4],[5,6]]]",
+        "0000000000000000000000000000000000000000000000000000000000000020\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         00000000000000000000000000000000000000000000000000000000000000a0\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000003\
+         0000000000000000000000000000000000000000000000000000000000000004\
+         0000000000000000000000000000000000000000000000000000000000000005\
+         0000000000000000000000000000000000000000000000000000000000000006",
+    ),
+    (
+        "((bool,uint16[])[],uint256)",
+        "([(true,[7]),(false,[8,9])],10)",
+        "0000000000000000000000000000000000000000000000000000000000000020\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         000000000000000000000000000000000000000000000000000000000000000a\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         00000000000000000000000000000000000000000000000000000000000000c0\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000007\
+         0000000000000000000000000000000000000000000000000000000000000000\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000008\
+         0000000000000000000000000000000000000000000000000000000000000009",
+    ),
+    (
+        "uint256[][][]",
+        "[[[1],[]],[],[[2,3]]]",
+        "0000000000000000000000000000000000000000000000000000000000000020\
+         0000000000000000000000000000000000000000000000000000000000000003\
+         0000000000000000000000000000000000000000000000000000000000000060\
+         0000000000000000000000000000000000000000000000000000000000000120\
+         0000000000000000000000000000000000000000000000000000000000000140\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000080\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000000\
+         0000000000000000000000000000000000000000000000000000000000000000\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000020\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000003",
+    ),
+    (
+        "(uint256,(address,uint8[2])[])[]",
+        "[(1,[(0x5b38da6a701c568545dcfcb03fcb875f56beddc4,[2,3])]),(4,[])]",
+        "0000000000000000000000000000000000000000000000000000000000000020\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000100\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000001\
+         0000000000000000000000005b38da6a701c568545dcfcb03fcb875f56beddc4\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000003\
+         0000000000000000000000000000000000000000000000000000000000000004\
+         0000000000000000000000000000000000000000000000000000000000000040\
+         0000000000000000000000000000000000000000000000000000000000000000",
+    ),
+];
+
+fn params<'a>(types: &[ParamType], values: &[&'a str]) -> Vec<(ParamType, &'a str)> {
+    types.iter().cloned().zip(values.iter().cloned()).collect()
+}
+
+#[test]
+fn test_encode_function_call_vectors() {
+    for (sig, values, expected) in VECTORS {
+        let function = parse_function(sig).unwrap();
+        let params = params(&function.input_types(), values);
+        let expected = expected.from_hex().unwrap();
+        assert_eq!(
+            encode_function_call(&function.name, &params).unwrap(),
+            expected,
+            "{}",
+            sig
+        );
+    }
+}
+
+#[test]
+fn test_encode_params_vectors() {
+    for (sig, values, expected) in VECTORS {
+        let function = parse_function(sig).unwrap();
+        let params = params(&function.input_types(), values);
+        let expected = expected.from_hex().unwrap();
+        assert_eq!(encode_params(&params).unwrap(), &expected[4..], "{}", sig);
+    }
+}
+
+#[test]
+fn test_params_encode_vectors() {
+    for (sig, values, expected) in VECTORS {
+        let function = parse_function(sig).unwrap();
+        let mut params = Params::new();
+        for (param_type, value_str) in function.input_types().into_iter().zip(values.iter()) {
+            params.push(param_type, value_str);
+        }
+        let expected = expected.from_hex().unwrap();
+        assert_eq!(params.encode().unwrap(), &expected[4..], "{}", sig);
+    }
+}
+
+#[test]
+fn test_nested_vectors() {
+    for (type_str, value_str, expected) in NESTED_VECTORS {
+        let param_type = ParamType::from_str(type_str).unwrap();
+        let expected = expected.from_hex().unwrap();
+        let encoded = encode_params(&[(param_type.clone(), value_str)]).unwrap();
+        assert_eq!(encoded, expected, "{}", type_str);
+        assert_eq!(
+            decode(&[param_type], &encoded),
+            Ok(vec![value_str.to_string()]),
+            "{}",
+            type_str
+        );
+    }
+}